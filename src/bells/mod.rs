@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub use super::common::{Hour, Room};
+pub use super::dsb::Duration;
+
+// tests as sub module
+#[cfg(test)] // only add when running tests
+mod test;
+
+/// config struct for the bell schedules
+#[derive(Debug, Clone, Serialize)]
+pub struct Config {
+    /// named bell schedules
+    pub schedules: BTreeMap<String, Schedule>,
+
+    /// name of the schedule used if nothing else matches
+    pub default: String,
+
+    /// schedule names per weekday
+    pub weekdays: BTreeMap<String, String>,
+
+    /// schedule names per building, win over the weekday schedules
+    pub buildings: BTreeMap<String, String>,
+}
+
+impl Config {
+    /// create a new instance of Config with the default schedule
+    pub fn new() -> Self {
+        let mut schedules = BTreeMap::new();
+        schedules.insert(
+            String::from("default"),
+            Schedule::from_strs(&[
+                "07:55-08:40",
+                "08:45-09:30",
+                "09:50-10:35",
+                "10:40-11:25",
+                "11:45-12:30",
+                "12:35-13:20",
+                "13:30-14:15",
+                "14:15-15:00",
+                "15:05-15:50",
+                "15:50-16:35",
+                "16:40-17:25",
+                "17:25-18:10",
+            ])
+            .unwrap(),
+        );
+        Self {
+            schedules,
+            default: String::from("default"),
+            weekdays: BTreeMap::new(),
+            buildings: BTreeMap::new(),
+        }
+    }
+
+    /// get the schedule of weekday in building
+    pub fn schedule(&self, weekday: Weekday, building: Option<&str>) -> Option<&Schedule> {
+        if let Some(building) = building {
+            if let Some(name) = self.buildings.get(&building.to_uppercase()) {
+                return self.schedules.get(name);
+            }
+        }
+        if let Some(name) = self.weekdays.get(&weekday_name(weekday)) {
+            return self.schedules.get(name);
+        }
+        self.schedules.get(&self.default)
+    }
+
+    /// get start and end of the periods in duration on date
+    pub fn times(
+        &self,
+        date: NaiveDate,
+        duration: &Duration,
+        building: Option<&str>,
+    ) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        let schedule = self.schedule(date.weekday(), building)?;
+        let from = schedule.period(duration.from)?;
+        let to = schedule.period(duration.to)?;
+        Some((date.and_time(from.start), date.and_time(to.end)))
+    }
+
+    /// get the lessons of the hours of a day, empty hours are skipped
+    pub fn lessons(&self, date: NaiveDate, hours: &[Hour]) -> Vec<Lesson> {
+        let mut ret = Vec::new();
+        for (i, hour) in hours.iter().enumerate() {
+            if hour.is_empty() {
+                continue;
+            }
+            let period = i as i16 + 1;
+            let duration = Duration {
+                from: period,
+                to: period,
+            };
+            if let Some((start, end)) = self.times(date, &duration, hour.room.building()) {
+                ret.push(Lesson {
+                    period,
+                    start,
+                    end,
+                    hour: hour.clone(),
+                });
+            }
+        }
+        ret
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// start and end of a period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Period {
+    pub period: i16,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl Period {
+    /// parse period times like `07:55-08:40`
+    pub fn from_str(period: i16, input: &str) -> Option<Self> {
+        let input: Vec<&str> = input.split('-').collect();
+        if input.len() != 2 {
+            return None;
+        }
+        Some(Self {
+            period,
+            start: NaiveTime::parse_from_str(input[0].trim(), "%H:%M").ok()?,
+            end: NaiveTime::parse_from_str(input[1].trim(), "%H:%M").ok()?,
+        })
+    }
+}
+
+/// bell schedule of a day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Schedule {
+    pub periods: Vec<Period>,
+}
+
+impl Schedule {
+    /// create schedule from period times, the first string is period 1
+    pub fn from_strs(input: &[&str]) -> Option<Self> {
+        let mut periods = Vec::new();
+        for (i, v) in input.iter().enumerate() {
+            periods.push(Period::from_str(i as i16 + 1, v)?);
+        }
+        Some(Self { periods })
+    }
+
+    /// get times of period
+    pub fn period(&self, period: i16) -> Option<&Period> {
+        self.periods.iter().find(|v| v.period == period)
+    }
+
+    /// get the period running at time
+    pub fn current(&self, time: NaiveTime) -> Option<&Period> {
+        self.periods
+            .iter()
+            .find(|v| v.start <= time && time < v.end)
+    }
+}
+
+/// hour of a timetable with its times
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lesson {
+    pub period: i16,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub hour: Hour,
+}
+
+/// get the config name of weekday (`mon`, `tue`, ...)
+pub fn weekday_name(weekday: Weekday) -> String {
+    match weekday {
+        Weekday::Mon => "mon",
+        Weekday::Tue => "tue",
+        Weekday::Wed => "wed",
+        Weekday::Thu => "thu",
+        Weekday::Fri => "fri",
+        Weekday::Sat => "sat",
+        Weekday::Sun => "sun",
+    }
+    .to_string()
+}
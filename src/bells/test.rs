@@ -0,0 +1,62 @@
+//! test file for the bell schedules
+
+use super::{Config, Duration, Schedule};
+use chrono::{NaiveDate, NaiveTime, Weekday};
+
+#[test]
+fn times() {
+    let conf = Config::new();
+    let (start, end) = conf
+        .times(
+            NaiveDate::from_ymd(2019, 7, 5),
+            &Duration { from: 1, to: 2 },
+            None,
+        )
+        .unwrap();
+    assert_eq!(start.time(), NaiveTime::from_hms(7, 55, 0));
+    assert_eq!(end.time(), NaiveTime::from_hms(9, 30, 0));
+}
+
+#[test]
+fn unknown_period() {
+    let conf = Config::new();
+    let times = conf.times(
+        NaiveDate::from_ymd(2019, 7, 5),
+        &Duration { from: 13, to: 13 },
+        None,
+    );
+    assert!(times.is_none());
+}
+
+#[test]
+fn building_before_weekday() {
+    let mut conf = Config::new();
+    conf.schedules.insert(
+        String::from("short"),
+        Schedule::from_strs(&["08:00-08:40"]).unwrap(),
+    );
+    conf.schedules.insert(
+        String::from("sport"),
+        Schedule::from_strs(&["08:10-08:55"]).unwrap(),
+    );
+    conf.weekdays
+        .insert(String::from("fri"), String::from("short"));
+    conf.buildings
+        .insert(String::from("TH"), String::from("sport"));
+
+    let schedule = conf.schedule(Weekday::Fri, None).unwrap();
+    assert_eq!(schedule.periods[0].start, NaiveTime::from_hms(8, 0, 0));
+    let schedule = conf.schedule(Weekday::Fri, Some("th")).unwrap();
+    assert_eq!(schedule.periods[0].start, NaiveTime::from_hms(8, 10, 0));
+    let schedule = conf.schedule(Weekday::Mon, None).unwrap();
+    assert_eq!(schedule.periods[0].start, NaiveTime::from_hms(7, 55, 0));
+}
+
+#[test]
+fn current() {
+    let conf = Config::new();
+    let schedule = conf.schedule(Weekday::Mon, None).unwrap();
+    let period = schedule.current(NaiveTime::from_hms(10, 0, 0)).unwrap();
+    assert_eq!(period.period, 3);
+    assert!(schedule.current(NaiveTime::from_hms(9, 40, 0)).is_none());
+}
@@ -0,0 +1,182 @@
+use chrono::prelude::*;
+
+/// event of an iCalendar file
+#[derive(Debug, Clone)]
+pub struct Event {
+    /// unique id of the event
+    pub uid: String,
+    /// start of the event
+    pub start: NaiveDateTime,
+    /// end of the event
+    pub end: NaiveDateTime,
+    pub summary: String,
+    pub location: String,
+    pub description: String,
+}
+
+impl Event {
+    /// create event with summary
+    pub fn new(uid: &str, start: NaiveDateTime, end: NaiveDateTime, summary: &str) -> Self {
+        Self {
+            uid: uid.to_string(),
+            start,
+            end,
+            summary: summary.to_string(),
+            location: String::new(),
+            description: String::new(),
+        }
+    }
+}
+
+/// parse events of an iCalendar file, unknown properties are ignored
+pub fn parse(input: &str) -> Vec<Event> {
+    let mut ret = Vec::new();
+    let mut event: Option<Event> = None;
+    let mut all_day = false;
+
+    for line in unfold(input).iter() {
+        let line: Vec<&str> = line.splitn(2, ':').collect();
+        if line.len() != 2 {
+            continue;
+        }
+        let mut params = line[0].split(';');
+        let name = params.next().unwrap_or("").to_uppercase();
+        let value = line[1].trim();
+
+        if name == "BEGIN" && value == "VEVENT" {
+            let time = NaiveDate::from_ymd(1970, 1, 1).and_hms(0, 0, 0);
+            event = Some(Event::new("", time, time, ""));
+            all_day = false;
+            continue;
+        }
+        if name == "END" && value == "VEVENT" {
+            if let Some(mut v) = event.take() {
+                if v.end <= v.start {
+                    // events without end last one day or no time
+                    v.end = if all_day {
+                        v.start + chrono::Duration::days(1)
+                    } else {
+                        v.start
+                    };
+                }
+                ret.push(v);
+            }
+            continue;
+        }
+
+        if let Some(event) = event.as_mut() {
+            match name.as_str() {
+                "UID" => event.uid = unescape(value),
+                "SUMMARY" => event.summary = unescape(value),
+                "LOCATION" => event.location = unescape(value),
+                "DESCRIPTION" => event.description = unescape(value),
+                "DTSTART" => match parse_time(value) {
+                    Some(time) => {
+                        event.start = time;
+                        all_day = value.len() == 8;
+                    }
+                    None => eprintln!("Error: ICal: could not parse DTSTART {{{}}}", value),
+                },
+                "DTEND" => match parse_time(value) {
+                    Some(time) => event.end = time,
+                    None => eprintln!("Error: ICal: could not parse DTEND {{{}}}", value),
+                },
+                _ => (),
+            }
+        }
+    }
+    ret
+}
+
+impl Event {
+    /// get all days touched by the event
+    pub fn dates(&self) -> Vec<NaiveDate> {
+        let mut end = self.end.date();
+        if self.end.time() != NaiveTime::from_hms(0, 0, 0) {
+            end = end.succ();
+        }
+        let mut ret = vec![self.start.date()];
+        let mut date = self.start.date().succ();
+        while date < end {
+            ret.push(date);
+            date = date.succ();
+        }
+        ret
+    }
+}
+
+/// join folded lines
+fn unfold(input: &str) -> Vec<String> {
+    let mut ret: Vec<String> = Vec::new();
+    for line in input.lines() {
+        let line = line.trim_end_matches('\r');
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some(last) = ret.last_mut() {
+                last.push_str(&line[1..]);
+                continue;
+            }
+        }
+        ret.push(line.to_string());
+    }
+    ret
+}
+
+/// parse date (`20190715`) or date time (`20190715T080000Z`) value
+fn parse_time(input: &str) -> Option<NaiveDateTime> {
+    let input = input.trim_end_matches('Z');
+    if input.len() == 8 {
+        return NaiveDate::parse_from_str(input, "%Y%m%d")
+            .ok()
+            .map(|v| v.and_hms(0, 0, 0));
+    }
+    NaiveDateTime::parse_from_str(input, "%Y%m%dT%H%M%S").ok()
+}
+
+/// unescape text values
+fn unescape(input: &str) -> String {
+    input
+        .replace("\\n", "\n")
+        .replace("\\N", "\n")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+/// create iCalendar file from events, times are written as local times
+pub fn write(name: &str, events: &[Event]) -> String {
+    let now = Utc::now().format("%Y%m%dT%H%M%SZ");
+    let mut ret = String::new();
+    ret += "BEGIN:VCALENDAR\r\n";
+    ret += "VERSION:2.0\r\n";
+    ret += &format!(
+        "PRODID:-//llgCompanion//{}//DE\r\n",
+        env!("CARGO_PKG_VERSION")
+    );
+    ret += &format!("X-WR-CALNAME:{}\r\n", escape(name));
+    for v in events.iter() {
+        ret += "BEGIN:VEVENT\r\n";
+        ret += &format!("UID:{}\r\n", escape(&v.uid));
+        ret += &format!("DTSTAMP:{}\r\n", now);
+        ret += &format!("DTSTART:{}\r\n", v.start.format("%Y%m%dT%H%M%S"));
+        ret += &format!("DTEND:{}\r\n", v.end.format("%Y%m%dT%H%M%S"));
+        ret += &format!("SUMMARY:{}\r\n", escape(&v.summary));
+        if !v.location.is_empty() {
+            ret += &format!("LOCATION:{}\r\n", escape(&v.location));
+        }
+        if !v.description.is_empty() {
+            ret += &format!("DESCRIPTION:{}\r\n", escape(&v.description));
+        }
+        ret += "END:VEVENT\r\n";
+    }
+    ret += "END:VCALENDAR\r\n";
+    ret
+}
+
+/// escape text values
+fn escape(input: &str) -> String {
+    input
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
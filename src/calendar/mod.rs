@@ -0,0 +1,275 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[doc(inline)]
+use super::error::Context;
+pub use super::error::Result;
+use super::storage::MongoDB;
+
+pub use super::dsb::{Week, DSB};
+pub use super::planinfo::{Hour, Table};
+
+/// iCalendar export
+pub mod ical;
+
+// tests as sub module
+#[cfg(test)] // only add when running tests
+mod test;
+
+/// config struct for the school calendar
+#[derive(Clone)]
+pub struct Config {
+    /// manual week types, applied to the whole week of the date
+    /// overrides the weeks learned from the dsb
+    pub overrides: Vec<(NaiveDate, Week)>,
+
+    /// days without school
+    pub holidays: BTreeSet<NaiveDate>,
+
+    /// iCalendar files with holidays, every day with an event is free
+    pub ical: Vec<String>,
+
+    /// number of days to derive and store in advance
+    pub days: i64,
+
+    /// verbose level
+    pub verbose: u8,
+}
+
+impl Config {
+    /// create a new instance of Config
+    pub fn new() -> Self {
+        Self {
+            overrides: Vec::new(),
+            holidays: BTreeSet::new(),
+            ical: Vec::new(),
+            days: 180,
+            verbose: 0,
+        }
+    }
+
+    /// read holidays from the iCalendar files
+    pub fn import(&mut self) -> Result<()> {
+        for path in self.ical.iter() {
+            let content = std::fs::read_to_string(path).context(path)?;
+            for event in ical::parse(&content) {
+                if self.verbose >= 3 {
+                    println!(
+                        "Debug3: Calendar: holiday {} ({} - {})",
+                        event.summary, event.start, event.end
+                    );
+                }
+                self.holidays.extend(event.dates());
+            }
+        }
+        Ok(())
+    }
+
+    /// test if there is school on date
+    pub fn is_school_day(&self, date: NaiveDate) -> bool {
+        Calendar::new(self).is_school_day(date)
+    }
+
+    /// create calendar with the weeks learned so far
+    pub fn load(&self, db: &MongoDB) -> Result<Calendar> {
+        let mut calendar = Calendar::new(self);
+        for v in db.calendar_read()? {
+            if v.source == WeekSource::Dsb {
+                calendar.learn(v.date, v.week);
+            }
+        }
+        Ok(calendar)
+    }
+
+    /// learn weeks from parsed dsb days and store the school days from today on
+    pub fn update(&self, db: &MongoDB, dsb: &[DSB]) -> Result<()> {
+        let mut calendar = self.load(db)?;
+        for v in dsb.iter() {
+            calendar.learn(v.date, v.week);
+        }
+
+        let today = Local::today().naive_local();
+        let days = calendar.school_days(today, today + chrono::Duration::days(self.days));
+        if self.verbose >= 3 {
+            println!("Debug3: Calendar: write {} school days", days.len());
+        }
+        db.calendar_write(&days)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// where the week type of a school day comes from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WeekSource {
+    /// read from the header of a dsb day
+    Dsb,
+    /// set in the config
+    Config,
+    /// counted from the next known week
+    Derived,
+}
+
+/// school day with its week type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchoolDay {
+    pub date: NaiveDate,
+    pub week: Week,
+    pub source: WeekSource,
+}
+
+/// school calendar, holding the known A/B weeks
+#[derive(Debug, Clone)]
+pub struct Calendar {
+    /// weeks set in the config, keyed by monday
+    overrides: BTreeMap<NaiveDate, Week>,
+
+    /// weeks learned from the dsb, keyed by monday
+    learned: BTreeMap<NaiveDate, Week>,
+
+    /// days without school
+    holidays: BTreeSet<NaiveDate>,
+}
+
+impl Calendar {
+    /// create a new calendar from config
+    pub fn new(conf: &Config) -> Self {
+        let mut overrides = BTreeMap::new();
+        for (date, week) in conf.overrides.iter() {
+            if week.is_ab() {
+                overrides.insert(monday(*date), *week);
+            }
+        }
+        Self {
+            overrides,
+            learned: BTreeMap::new(),
+            holidays: conf.holidays.clone(),
+        }
+    }
+
+    /// learn week type of date, ignores days without A/B week
+    pub fn learn(&mut self, date: NaiveDate, week: Week) {
+        if week.is_ab() {
+            self.learned.insert(monday(date), week);
+        }
+    }
+
+    /// add a day without school
+    pub fn add_holiday(&mut self, date: NaiveDate) {
+        self.holidays.insert(date);
+    }
+
+    /// test if there is school on date
+    pub fn is_school_day(&self, date: NaiveDate) -> bool {
+        date.weekday().num_days_from_monday() < 5 && !self.holidays.contains(&date)
+    }
+
+    /// test if there is school on any day of the week of date
+    fn is_school_week(&self, date: NaiveDate) -> bool {
+        let monday = monday(date);
+        (0..5).any(|v| self.is_school_day(monday + chrono::Duration::days(v)))
+    }
+
+    /// get week type of date, `None` on days without school or without known week
+    pub fn week(&self, date: NaiveDate) -> Option<Week> {
+        self.school_day(date).map(|v| v.week)
+    }
+
+    /// get school day of date, `None` on days without school or without known week
+    pub fn school_day(&self, date: NaiveDate) -> Option<SchoolDay> {
+        if !self.is_school_day(date) {
+            return None;
+        }
+        let target = monday(date);
+        if let Some(week) = self.overrides.get(&target) {
+            return Some(SchoolDay {
+                date,
+                week: *week,
+                source: WeekSource::Config,
+            });
+        }
+        if let Some(week) = self.learned.get(&target) {
+            return Some(SchoolDay {
+                date,
+                week: *week,
+                source: WeekSource::Dsb,
+            });
+        }
+
+        let (anchor, week) = self.nearest_anchor(target)?;
+        // count the school weeks between the anchor and the target
+        let mut count = 0;
+        let (mut monday, end) = if anchor < target {
+            (anchor + chrono::Duration::weeks(1), target)
+        } else {
+            (target, anchor - chrono::Duration::weeks(1))
+        };
+        while monday <= end {
+            if self.is_school_week(monday) {
+                count += 1;
+            }
+            monday += chrono::Duration::weeks(1);
+        }
+
+        let mut week = week;
+        if count % 2 == 1 {
+            week = week.other();
+        }
+        Some(SchoolDay {
+            date,
+            week,
+            source: WeekSource::Derived,
+        })
+    }
+
+    /// get all school days from `from` to `to` (inclusive)
+    pub fn school_days(&self, from: NaiveDate, to: NaiveDate) -> Vec<SchoolDay> {
+        let mut ret = Vec::new();
+        let mut date = from;
+        while date <= to {
+            if let Some(day) = self.school_day(date) {
+                ret.push(day);
+            }
+            date = date.succ();
+        }
+        ret
+    }
+
+    /// get the hours of table for date, picks the table of the week type
+    pub fn table_day<'a>(&self, table: &'a Table, date: NaiveDate) -> Option<&'a [Hour]> {
+        let week = self.week(date)?;
+        table.day(date.weekday(), &week)
+    }
+
+    /// find the known week next to monday, overrides win over learned weeks
+    fn nearest_anchor(&self, monday: NaiveDate) -> Option<(NaiveDate, Week)> {
+        let mut anchors = self.learned.clone();
+        for (k, v) in self.overrides.iter() {
+            anchors.insert(*k, *v);
+        }
+        let before = anchors.range(..monday).next_back();
+        let after = anchors.range(monday..).next();
+        match (before, after) {
+            (Some(before), Some(after)) => {
+                if monday - *before.0 <= *after.0 - monday {
+                    Some((*before.0, *before.1))
+                } else {
+                    Some((*after.0, *after.1))
+                }
+            }
+            (Some(v), None) | (None, Some(v)) => Some((*v.0, *v.1)),
+            (None, None) => None,
+        }
+    }
+}
+
+/// get monday of the week of date
+fn monday(date: NaiveDate) -> NaiveDate {
+    date - chrono::Duration::days(i64::from(date.weekday().num_days_from_monday()))
+}
@@ -0,0 +1,125 @@
+//! test file for the school calendar
+
+use super::{Calendar, Config, Week, WeekSource};
+use chrono::NaiveDate;
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd(y, m, d)
+}
+
+/// calendar knowing that the week of the 2019-07-05 is a B week
+fn calendar() -> Calendar {
+    let mut calendar = Calendar::new(&Config::new());
+    calendar.learn(date(2019, 7, 5), Week::B);
+    calendar
+}
+
+#[test]
+fn learned_week() {
+    let calendar = calendar();
+    let day = calendar.school_day(date(2019, 7, 1)).unwrap();
+    assert_eq!(day.week, Week::B);
+    assert_eq!(day.source, WeekSource::Dsb);
+}
+
+#[test]
+fn derived_week() {
+    let calendar = calendar();
+    let day = calendar.school_day(date(2019, 7, 8)).unwrap();
+    assert_eq!(day.week, Week::A);
+    assert_eq!(day.source, WeekSource::Derived);
+    assert_eq!(calendar.week(date(2019, 6, 24)), Some(Week::A));
+    assert_eq!(calendar.week(date(2019, 6, 17)), Some(Week::B));
+}
+
+#[test]
+fn weekend() {
+    let calendar = calendar();
+    assert_eq!(calendar.week(date(2019, 7, 6)), None);
+}
+
+#[test]
+fn skip_holiday_week() {
+    let mut calendar = calendar();
+    for v in 8..13 {
+        calendar.add_holiday(date(2019, 7, v));
+    }
+    assert_eq!(calendar.week(date(2019, 7, 10)), None);
+    assert_eq!(calendar.week(date(2019, 7, 15)), Some(Week::A));
+}
+
+#[test]
+fn override_wins() {
+    let mut conf = Config::new();
+    conf.overrides.push((date(2019, 7, 3), Week::A));
+    let mut calendar = Calendar::new(&conf);
+    calendar.learn(date(2019, 7, 5), Week::B);
+    let day = calendar.school_day(date(2019, 7, 5)).unwrap();
+    assert_eq!(day.week, Week::A);
+    assert_eq!(day.source, WeekSource::Config);
+}
+
+#[test]
+fn no_anchor() {
+    let calendar = Calendar::new(&Config::new());
+    assert_eq!(calendar.week(date(2019, 7, 5)), None);
+}
+
+mod ical {
+    use super::super::ical;
+    use super::date;
+
+    const HOLIDAYS: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+BEGIN:VEVENT\r\n\
+UID:sommerferien-2019\r\n\
+DTSTART;VALUE=DATE:20190715\r\n\
+DTEND;VALUE=DATE:20190828\r\n\
+SUMMARY:Sommerferien\r\n\
+\x20 Nordrhein-Westfalen\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+DTSTART;VALUE=DATE:20191003\r\n\
+SUMMARY:Tag der Deutschen Einheit\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+DTSTART:20191104T080000Z\r\n\
+DTEND:20191104T130000Z\r\n\
+SUMMARY:P\\, Tag\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn parse() {
+        let events = ical::parse(HOLIDAYS);
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].uid, "sommerferien-2019");
+        assert_eq!(events[0].summary, "Sommerferien Nordrhein-Westfalen");
+        assert_eq!(events[2].summary, "P, Tag");
+    }
+
+    #[test]
+    fn dates() {
+        let events = ical::parse(HOLIDAYS);
+        let dates = events[0].dates();
+        assert_eq!(dates.len(), 44);
+        assert_eq!(dates[0], date(2019, 7, 15));
+        assert_eq!(dates[43], date(2019, 8, 27));
+        assert_eq!(events[1].dates(), vec![date(2019, 10, 3)]);
+        assert_eq!(events[2].dates(), vec![date(2019, 11, 4)]);
+    }
+
+    #[test]
+    fn holidays_skip_school_days() {
+        let mut calendar = super::calendar();
+        for event in ical::parse(HOLIDAYS) {
+            for v in event.dates() {
+                calendar.add_holiday(v);
+            }
+        }
+        assert_eq!(calendar.week(date(2019, 10, 3)), None);
+        // the 2019-07-05 is a B week, the holiday weeks are not counted
+        assert_eq!(calendar.week(date(2019, 7, 8)), Some(super::Week::A));
+        assert_eq!(calendar.week(date(2019, 8, 28)), Some(super::Week::B));
+    }
+}
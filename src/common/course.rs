@@ -0,0 +1,250 @@
+use super::{Course, CourseKind};
+use crate::error::{Error, Result};
+
+/// layout of the course strings of the different plans
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Layout {
+    /// substitution plan, class and course (`Q1`, `BI-GK4`)
+    /// Sec 2 courses are named by the subject, Sec 1 courses by the course
+    Dsb,
+    /// teacher, room and class plans, group and course (`Q1-G3`, `M-GK1`)
+    /// courses are named by subject and grade (`M Q1`, `D 5a`)
+    Planinfo,
+    /// student plans, track and course (`(G3)`, `PH-GK3`)
+    /// courses are named by the subject
+    Students,
+}
+
+/// grade of a course
+#[derive(Debug, Clone, PartialEq)]
+pub enum Grade {
+    /// class of the lower school (`5a`, `7ad`)
+    Sec1(String),
+    /// year of the upper school (`EF`, `Q1`, `Q2`)
+    Sec2(String),
+}
+
+/// group part of a course string (`Q1-G3`, `(G3)`, `5a`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Group {
+    pub grade: Option<Grade>,
+    /// track of the course, 0 if unknown
+    pub track: i16,
+}
+
+/// course part of a course string (`M-GK1`, `SWe-LK2`, `F6-1`, `D`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spec {
+    pub subject: String,
+    pub kind: CourseKind,
+    /// group number of Sec 1 courses (`F6-1`)
+    pub number: Option<i16>,
+}
+
+/// years of the upper school
+const GRADES: &[&str] = &["EF", "Q1", "Q2"];
+
+/// highest class of the lower school
+const MAX_SEC1_CLASS: u32 = 10;
+
+/// constructor of a course kind from the number
+type KindFn = fn(i16) -> CourseKind;
+
+/// course kinds of the upper school by prefix
+const KINDS: &[(&str, KindFn)] = &[("GK", basic_course), ("LK", advanced_course)];
+
+/// words marking an exam (`Klausur D-GK1`)
+const EXAM_MARKERS: &[&str] = &["klausur", "klausuren", "(k)"];
+
+/// separators of the parts of a group
+const GROUP_SEPARATORS: &[char] = &['-', '/', ',', ' '];
+
+/// part of a group
+enum Part {
+    Grade(Grade),
+    Track(i16),
+}
+
+/// rules to classify the parts of a group, the first match wins
+const GROUP_RULES: &[fn(&str) -> Option<Part>] = &[upper_grade, lower_class, track];
+
+fn basic_course(number: i16) -> CourseKind {
+    CourseKind::GK { number }
+}
+
+fn advanced_course(number: i16) -> CourseKind {
+    CourseKind::LK { number }
+}
+
+/// `EF`, `Q1`, `Q2`
+fn upper_grade(part: &str) -> Option<Part> {
+    GRADES
+        .iter()
+        .find(|v| v.eq_ignore_ascii_case(part))
+        .map(|v| Part::Grade(Grade::Sec2(v.to_string())))
+}
+
+/// `5a`, `7ad`, `10`
+fn lower_class(part: &str) -> Option<Part> {
+    let split = part
+        .find(|v: char| !v.is_ascii_digit())
+        .unwrap_or(part.len());
+    let (year, classes) = part.split_at(split);
+    match year.parse::<u32>() {
+        Ok(year)
+            if (5..=MAX_SEC1_CLASS).contains(&year)
+                && classes.chars().all(|v| v.is_ascii_alphabetic()) =>
+        {
+            Some(Part::Grade(Grade::Sec1(part.to_string())))
+        }
+        _ => None,
+    }
+}
+
+/// `G3`, `3`
+fn track(part: &str) -> Option<Part> {
+    let number = part.trim_start_matches(['G', 'g']);
+    if number.is_empty() || !number.chars().all(|v| v.is_ascii_digit()) {
+        return None;
+    }
+    number.parse().ok().map(Part::Track)
+}
+
+/// error for input not matching the grammar
+fn not_parsable(input: &str, reason: &str) -> Error {
+    Error::new_course_not_parsable(format!("{{{}}} {}", input, reason))
+}
+
+/// remove the exam markers of input, returns if there was one
+pub fn strip_exam(input: &str) -> (String, bool) {
+    let mut exam = false;
+    let mut parts = Vec::new();
+    for part in input.split_whitespace() {
+        if EXAM_MARKERS.iter().any(|v| v.eq_ignore_ascii_case(part)) {
+            exam = true;
+        } else {
+            parts.push(part);
+        }
+    }
+    (parts.join(" "), exam)
+}
+
+impl Group {
+    /// parse group (`Q1`, `Q1-G3`, `(G3)`, `()`, `5a`)
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut ret = Self::default();
+        let trimmed = input.trim().trim_start_matches('(').trim_end_matches(')');
+        for part in trimmed.split(GROUP_SEPARATORS).filter(|v| !v.is_empty()) {
+            match GROUP_RULES.iter().find_map(|rule| rule(part)) {
+                Some(Part::Grade(grade)) => ret.grade = Some(grade),
+                Some(Part::Track(track)) => ret.track = track,
+                None => return Err(not_parsable(input, "unknown group")),
+            }
+        }
+        Ok(ret)
+    }
+}
+
+impl Spec {
+    /// parse course (`M-GK1`, `SWe-LK2`, `F6-1`, `D`)
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut parts = input.trim().split('-');
+        let subject = parts.next().unwrap_or_default();
+        if !subject.starts_with(|v: char| v.is_alphabetic())
+            || !subject.chars().all(|v| v.is_alphanumeric() || v == '_')
+        {
+            return Err(not_parsable(input, "invalid subject"));
+        }
+
+        let mut ret = Self {
+            subject: subject.to_string(),
+            kind: CourseKind::None,
+            number: None,
+        };
+        if let Some(part) = parts.next() {
+            if let Ok(kind) = CourseKind::parse(part) {
+                ret.kind = kind;
+            } else if let Ok(number) = part.parse() {
+                ret.number = Some(number);
+            } else {
+                return Err(not_parsable(input, "unknown course kind"));
+            }
+        }
+        if parts.next().is_some() {
+            return Err(not_parsable(input, "too many parts"));
+        }
+        Ok(ret)
+    }
+}
+
+impl CourseKind {
+    /// parse course kind (`GK1`, `LK2`, `GK11`)
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim();
+        for (prefix, kind) in KINDS.iter() {
+            if input.len() > prefix.len()
+                && input.is_char_boundary(prefix.len())
+                && input[..prefix.len()].eq_ignore_ascii_case(prefix)
+            {
+                return match input[prefix.len()..].parse() {
+                    Ok(number) if number >= 0 => Ok(kind(number)),
+                    _ => Err(not_parsable(input, "invalid course number")),
+                };
+            }
+        }
+        Err(not_parsable(input, "unknown course kind"))
+    }
+}
+
+impl Course {
+    /// parse course of group (`Q1-G3`, `(G3)`, `5a`) and course (`M-GK1`, `D`)
+    /// exams are marked with `Klausur` in one of them
+    /// empty courses are `Course::None`
+    pub fn parse(layout: Layout, group: &str, course: &str) -> Result<Self> {
+        let (group, group_exam) = strip_exam(group);
+        let (course, course_exam) = strip_exam(course);
+        let exam = group_exam || course_exam;
+        let course = course.trim_matches('_');
+        if course.is_empty() {
+            if exam {
+                return Err(not_parsable(&group, "exam without course"));
+            }
+            return Ok(Course::None);
+        }
+
+        if let Some(special) = Course::from_special_str(&group, course) {
+            return Ok(special);
+        }
+
+        let spec = Spec::parse(course)?;
+        let parsed = Group::parse(&group)?;
+        let grade = match parsed.grade {
+            Some(Grade::Sec1(class)) => {
+                return Ok(Course::Sec1 {
+                    name: match layout {
+                        Layout::Dsb => course.to_string(),
+                        Layout::Planinfo | Layout::Students => format!("{} {}", course, class),
+                    },
+                });
+            }
+            Some(Grade::Sec2(grade)) => Some(grade),
+            None => None,
+        };
+        if spec.number.is_some() {
+            return Err(not_parsable(course, "group number in upper school"));
+        }
+
+        let name = match (layout, grade) {
+            (Layout::Dsb, _) => spec.subject.to_uppercase(),
+            (Layout::Planinfo, Some(grade)) => format!("{} {}", spec.subject, grade),
+            (Layout::Planinfo, None) | (Layout::Students, _) => spec.subject,
+        };
+        let track = parsed.track;
+        let kind = spec.kind;
+        if exam {
+            Ok(Course::Sec2Exam { track, name, kind })
+        } else {
+            Ok(Course::Sec2 { track, name, kind })
+        }
+    }
+}
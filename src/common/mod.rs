@@ -1,13 +1,28 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
-#[derive(Debug, Clone, Serialize)]
-pub enum Room {
-    None,
-    A { room: i16 },
-    B { room: i16 },
-    C { room: i16 },
-    D { room: i16 },
-    E { room: i16 },
+/// grammar of the course strings of the plans
+pub mod course;
+
+pub use course::Layout;
+
+// tests as sub module
+#[cfg(test)] // only add when running tests
+mod test;
+
+/// room with its building
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "RoomDocument")]
+pub struct Room {
+    /// building of the room (`A`, `TH`), empty if unknown
+    pub building: String,
+
+    /// identifier of the room as used in the plans (`A114`, `A10a`, `TH1`)
+    /// empty if there is no room
+    pub id: String,
+
+    /// free-form name (`Aula`, `Turnhalle 1`)
+    pub name: Option<String>,
 }
 
 impl Room {
@@ -15,59 +30,295 @@ impl Room {
     pub fn new() -> Self {
         Default::default()
     }
-    /// parse dbs string
+
+    /// parse dbs string with the default parser
     pub fn from_dsb_str(input: &str) -> Self {
-        if input.len() != 4 {
-            eprintln!("Error: DSB: Room: from_str: {} not len 4", input);
-            return Room::None;
-        }
-
-        let room: &str = input.split_at(1).1;
-        let room: i16 = room.parse().unwrap_or(0);
-
-        if input.to_lowercase().trim().starts_with("a") {
-            return Room::A { room };
-        } else if input.to_lowercase().trim().starts_with("b") {
-            return Room::B { room };
-        } else if input.to_lowercase().trim().starts_with("c") {
-            return Room::C { room };
-        } else if input.to_lowercase().trim().starts_with("d") {
-            return Room::D { room };
-        } else if input.to_lowercase().trim().starts_with("e") {
-            return Room::E { room };
+        RoomParser::new().parse(input)
+    }
+
+    /// test if input looks like a room (`A114`) for the default parser
+    pub fn is_room_str(input: &str) -> bool {
+        RoomParser::new().is_room(input)
+    }
+
+    /// test if there is no room
+    pub fn is_none(&self) -> bool {
+        self.id.is_empty()
+    }
+
+    /// get the building of the room, None if unknown
+    pub fn building(&self) -> Option<&str> {
+        if self.building.is_empty() {
+            None
         } else {
-            eprintln!("Error: DSB: Room: from_str: could not parse {{{}}}", input);
-            return Room::None;
+            Some(&self.building)
+        }
+    }
+
+    /// test if other is the same room, the name is ignored
+    pub fn same(&self, other: &Room) -> bool {
+        self.id.eq_ignore_ascii_case(&other.id)
+    }
+
+    /// get the room as one of the old variants, None if it does not fit into one
+    pub fn legacy(&self) -> Option<LegacyRoom> {
+        if self.is_none() {
+            return Some(LegacyRoom::None);
+        }
+        let building = self.building.to_uppercase();
+        let number = self.id.get(1..)?;
+        if building.len() != 1
+            || !self.id.to_uppercase().starts_with(&building)
+            || number.len() != 3
+            || !number.chars().all(|v| v.is_ascii_digit())
+        {
+            return None;
+        }
+        let room: i16 = number.parse().ok()?;
+        match building.as_str() {
+            "A" => Some(LegacyRoom::A { room }),
+            "B" => Some(LegacyRoom::B { room }),
+            "C" => Some(LegacyRoom::C { room }),
+            "D" => Some(LegacyRoom::D { room }),
+            "E" => Some(LegacyRoom::E { room }),
+            _ => None,
         }
     }
 }
 
+impl fmt::Display for Room {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.id)
+    }
+}
+
 impl Default for Room {
     fn default() -> Self {
-        Room::None
+        Self {
+            building: String::new(),
+            id: String::new(),
+            name: None,
+        }
+    }
+}
+
+/// rooms as they were stored before, buildings `A` to `E` with a number
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LegacyRoom {
+    None,
+    A { room: i16 },
+    B { room: i16 },
+    C { room: i16 },
+    D { room: i16 },
+    E { room: i16 },
+}
+
+impl From<LegacyRoom> for Room {
+    fn from(room: LegacyRoom) -> Self {
+        let (building, room) = match room {
+            LegacyRoom::None => return Room::new(),
+            LegacyRoom::A { room } => ('A', room),
+            LegacyRoom::B { room } => ('B', room),
+            LegacyRoom::C { room } => ('C', room),
+            LegacyRoom::D { room } => ('D', room),
+            LegacyRoom::E { room } => ('E', room),
+        };
+        Room {
+            building: building.to_string(),
+            id: format!("{}{:03}", building, room),
+            name: None,
+        }
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// stored room, either the current struct or one of the old variants
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RoomDocument {
+    Room {
+        building: String,
+        id: String,
+        #[serde(default)]
+        name: Option<String>,
+    },
+    Legacy(LegacyRoom),
+}
+
+impl From<RoomDocument> for Room {
+    fn from(doc: RoomDocument) -> Self {
+        match doc {
+            RoomDocument::Room { building, id, name } => Room { building, id, name },
+            RoomDocument::Legacy(room) => room.into(),
+        }
+    }
+}
+
+/// pattern for rooms of a school (`TH*`), `*` matches the rest of the identifier
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoomPattern {
+    /// patterns without `*` match the whole identifier (`Sportplatz`), ignoring the case
+    pub pattern: String,
+
+    /// building of the matching rooms
+    pub building: String,
+
+    /// name of the matching rooms, `*` is replaced by the matched part (`Turnhalle *`)
+    pub name: Option<String>,
+}
+
+impl RoomPattern {
+    /// get the part of input matched by `*`, None if input does not match
+    /// exact patterns match an empty part
+    fn matches<'a>(&self, input: &'a str) -> Option<&'a str> {
+        let star = match self.pattern.find('*') {
+            Some(star) => star,
+            None if self.pattern.eq_ignore_ascii_case(input) => return Some(""),
+            None => return None,
+        };
+        let prefix = &self.pattern[..star];
+        let suffix = &self.pattern[star + 1..];
+        if input.len() <= prefix.len() + suffix.len()
+            || !input.is_char_boundary(prefix.len())
+            || !input.is_char_boundary(input.len() - suffix.len())
+            || !input[..prefix.len()].eq_ignore_ascii_case(prefix)
+            || !input[input.len() - suffix.len()..].eq_ignore_ascii_case(suffix)
+        {
+            return None;
+        }
+        Some(&input[prefix.len()..input.len() - suffix.len()])
+    }
+}
+
+/// parser for the rooms of a school
+/// aliases win over patterns, rooms matching nothing get the leading letters as building
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RoomParser {
+    /// patterns, the first matching pattern is used
+    #[serde(default)]
+    pub patterns: Vec<RoomPattern>,
+
+    /// names for rooms (`Aula` -> `A001`), the target is parsed with the patterns
+    #[serde(default)]
+    pub aliases: std::collections::BTreeMap<String, String>,
+}
+
+impl RoomParser {
+    /// create parser without patterns and aliases
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// parse room string (`A114`, `TH1`, `Aula`), `---` and empty input is no room
+    pub fn parse(&self, input: &str) -> Room {
+        let input = input.trim().trim_matches('-').trim();
+        if input.is_empty() {
+            return Room::new();
+        }
+
+        if let Some((alias, target)) = self
+            .aliases
+            .iter()
+            .find(|(alias, _)| alias.eq_ignore_ascii_case(input))
+        {
+            let mut room = self.parse_pattern(target.trim());
+            if room.name.is_none() {
+                room.name = Some(alias.to_string());
+            }
+            return room;
+        }
+
+        self.parse_pattern(input)
+    }
+
+    /// parse input with the patterns, falls back to the leading letters as building
+    fn parse_pattern(&self, input: &str) -> Room {
+        for pattern in self.patterns.iter() {
+            if let Some(matched) = pattern.matches(input) {
+                return Room {
+                    building: pattern.building.clone(),
+                    id: input.to_string(),
+                    name: pattern.name.as_ref().map(|v| v.replace('*', matched)),
+                };
+            }
+        }
+
+        Room {
+            building: default_building(input).unwrap_or("").to_uppercase(),
+            id: input.to_string(),
+            name: None,
+        }
+    }
+
+    /// test if input is a room, an alias, a pattern or letters followed by a number (`A114`)
+    pub fn is_room(&self, input: &str) -> bool {
+        let input = input.trim();
+        self.aliases.keys().any(|v| v.eq_ignore_ascii_case(input))
+            || self.patterns.iter().any(|v| v.matches(input).is_some())
+            || default_building(input).is_some()
+    }
+}
+
+/// get the leading letters of input if a digit follows them (`A` of `A10a`)
+fn default_building(input: &str) -> Option<&str> {
+    let pos = input.find(|v: char| !v.is_ascii_alphabetic())?;
+    if pos == 0 || pos > 3 || !input[pos..].starts_with(|v: char| v.is_ascii_digit()) {
+        return None;
+    }
+    Some(&input[..pos])
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Teacher {
+    /// short name as used in the plans (`MUE`)
     pub name: String,
+
+    /// details from the teacher directory, only set when serving
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<TeacherDetails>,
 }
 
 impl Teacher {
     pub fn new() -> Self {
         Self {
             name: String::new(),
+            details: None,
+        }
+    }
+
+    /// get the full name if known, else the short name
+    pub fn display_name(&self) -> String {
+        match self.details.as_ref().and_then(|v| v.name.as_ref()) {
+            Some(name) => name.to_string(),
+            None => self.name.clone(),
         }
     }
 }
 
+/// details of a teacher from the teacher directory
+/// name, salutation and email are only set if the names are public
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TeacherDetails {
+    /// full name
+    pub name: Option<String>,
+
+    /// salutation (`Frau`, `Herr`)
+    #[serde(default)]
+    pub salutation: Option<String>,
+
+    /// subjects taught (`D`, `GE`)
+    pub subjects: Vec<String>,
+
+    /// email, mostly contains the full name
+    pub email: Option<String>,
+}
+
 impl Default for Teacher {
     fn default() -> Self {
         Self::new()
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Hour {
     /// room where the period takes place
     pub room: Room,
@@ -86,28 +337,18 @@ impl Hour {
     pub fn new() -> Self {
         Default::default()
     }
-    pub fn parse_planinfo_teacher(&mut self, input: &str, teacher: &str) {
+    pub fn parse_planinfo_teacher(&mut self, input: &str, teacher: &str, rooms: &RoomParser) {
         let input = input.trim();
         self.teacher.name = teacher.to_string();
         self.is_tutor = false;
         if input.is_empty() {
             return;
         }
-        if input.contains("LZ")
-            || input.contains("BS")
-            || input.contains("VBS")
-            || input.contains("UEMI")
-            || input.contains("SPI")
-            || input.contains("AG")
-        {
-            eprintln!("Error: Hour: planInfo_Teacher: no parser for {}", input);
-            self.course = Course::Sec1 {
-                name: input.to_string(),
-            };
-            return;
-        }
 
         let inVec: Vec<&str> = input.split_ascii_whitespace().collect();
+        if self.parse_planinfo_special(&inVec, Some(rooms)) {
+            return;
+        }
         if inVec.len() != 3 {
             eprintln!(
                 "Error: Hour: planInfo_Teacher: vec len not 3 but {}",
@@ -116,37 +357,25 @@ impl Hour {
             return;
         }
 
-        let sClass: u32 = (input.as_bytes()[0] as u32 - '0' as u32) as u32;
-        if sClass > 0 && sClass < 9 {
-            self.course = Course::Sec1 {
-                name: format!("{} {}", inVec[1], inVec[0]),
-            };
-        } else {
-            self.course = Course::from_planinfo_teacher_str(inVec[0], inVec[1]);
+        match Course::parse(Layout::Planinfo, inVec[0], inVec[1]) {
+            Ok(course) => self.course = course,
+            Err(err) => eprintln!("Error: Hour: planInfo_Teacher: {}", err),
         }
-        self.room = Room::from_dsb_str(inVec[2].trim());
+        self.room = rooms.parse(inVec[2]);
     }
-    pub fn parse_planinfo_room(&mut self, input: &str, room: &str) {
+    pub fn parse_planinfo_room(&mut self, input: &str, room: &str, rooms: &RoomParser) {
         let input = input.trim();
         let room = room.trim();
         self.is_tutor = false;
-        if input.contains("LZ")
-            || input.contains("BS")
-            || input.contains("VBS")
-            || input.contains("UEMI")
-            || input.contains("SPI")
-            || input.contains("AG")
-        {
-            eprintln!("Error: Hour: planInfo_Room: no parser for {}", input);
-            self.course = Course::Sec1 {
-                name: input.to_string(),
-            };
+        if input.is_empty() {
             return;
         }
-
-        self.room = Room::from_dsb_str(input);
+        self.room = rooms.parse(room);
 
         let inVec: Vec<&str> = input.split_ascii_whitespace().collect();
+        if self.parse_planinfo_special(&inVec, None) {
+            return;
+        }
         if inVec.len() != 3 {
             eprintln!(
                 "Error: Hour: planInfo_Room: vec len not 3 but {}",
@@ -155,16 +384,80 @@ impl Hour {
             return;
         }
 
-        let sClass: u32 = (input.as_bytes()[0] as u32 - '0' as u32) as u32;
-        if sClass > 0 && sClass < 9 {
+        match Course::parse(Layout::Planinfo, inVec[0], inVec[1]) {
+            Ok(course) => self.course = course,
+            Err(err) => eprintln!("Error: Hour: planInfo_Room: {}", err),
+        }
+    }
+    pub fn parse_planinfo_class(&mut self, input: &str, class: &str, rooms: &RoomParser) {
+        let input = input.trim();
+        let class = class.trim();
+        self.is_tutor = false;
+        if input.is_empty() {
+            return;
+        }
+
+        let inVec: Vec<&str> = input.split_ascii_whitespace().collect();
+        if inVec.len() != 3 && inVec.len() != 2 {
+            eprintln!(
+                "Error: Hour: planInfo_Class: vec len not 2 or 3 but {}",
+                inVec.len()
+            );
             self.course = Course::Sec1 {
-                name: format!("{} {}", inVec[1], inVec[0]),
+                name: input.to_string(),
             };
-        } else {
-            self.course = Course::from_planinfo_room_str(inVec[0], inVec[1]);
+            return;
+        }
+
+        self.course = match Course::parse(Layout::Planinfo, class, inVec[0]) {
+            Ok(course) => course,
+            Err(err) => {
+                eprintln!("Error: Hour: planInfo_Class: {}", err);
+                Course::Sec1 {
+                    name: format!("{} {}", inVec[0], class),
+                }
+            }
+        };
+        self.teacher.name = inVec[1].to_string();
+        if let Some(room) = inVec.get(2) {
+            self.room = rooms.parse(room);
         }
     }
-    pub fn parse_planinfo_student(&mut self, input: &str, courseString: &str) {
+    /// parse special Sec 1 courses (`LZ`, `BS`, `VBS`, `UEMI`, `SPI`, `AG`)
+    /// the tokens are group, subject and room (with rooms) or teacher
+    /// returns false if the tokens are no special course
+    fn parse_planinfo_special(&mut self, tokens: &[&str], rooms: Option<&RoomParser>) -> bool {
+        let (group, mut rest) = match tokens.split_first() {
+            Some((group, rest)) => (*group, rest),
+            None => return false,
+        };
+        if !Course::is_special_str(group) && !rest.iter().any(|v| Course::is_special_str(v)) {
+            return false;
+        }
+
+        if let Some((last, others)) = rest.split_last() {
+            match rooms {
+                Some(rooms) if rooms.is_room(last) => {
+                    self.room = rooms.parse(last);
+                    rest = others;
+                }
+                None if !others.is_empty() => {
+                    self.teacher.name = last.to_string();
+                    rest = others;
+                }
+                _ => (),
+            }
+        }
+
+        match Course::from_special_str(group, &rest.join(" ")) {
+            Some(course) => {
+                self.course = course;
+                true
+            }
+            None => false,
+        }
+    }
+    pub fn parse_planinfo_student(&mut self, input: &str, courseString: &str, rooms: &RoomParser) {
         let input = input.trim();
         let courseString = courseString.trim();
 
@@ -176,11 +469,21 @@ impl Hour {
             );
             return;
         }
-        self.room = Room::from_dsb_str(inVec[3]);
+        self.room = rooms.parse(inVec[3]);
 
         self.teacher.name = inVec[2].to_string();
         self.is_tutor = courseString.contains(&self.teacher.name);
-        self.course = Course::from_planinfo_students_str(inVec[0], inVec[1]);
+        match Course::parse(Layout::Students, inVec[0], inVec[1]) {
+            Ok(course) => self.course = course,
+            Err(err) => eprintln!("Error: Hour: planInfo_Student: {}", err),
+        }
+    }
+}
+
+impl Hour {
+    /// test if nothing takes place in this hour
+    pub fn is_empty(&self) -> bool {
+        matches!(self.course, Course::None)
     }
 }
 
@@ -195,7 +498,7 @@ impl Default for Hour {
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Course {
     None,
     Sec1 {
@@ -211,6 +514,21 @@ pub enum Course {
         name: String,
         kind: CourseKind,
     },
+    /// learning time (`LZ`)
+    LearningTime {
+        group: String,
+        subject: String,
+    },
+    /// supervision and standby (`BS`, `VBS`, `UEMI`)
+    Supervision {
+        group: String,
+        subject: String,
+    },
+    /// working groups (`AG`, `SPI`)
+    WorkingGroup {
+        group: String,
+        subject: String,
+    },
 }
 
 impl Course {
@@ -218,119 +536,101 @@ impl Course {
     pub fn new() -> Self {
         Course::None
     }
-    /// parse dsb to Course
-    pub fn from_dsb_str(class: &str, course: &str) -> Self {
-        let course = course.trim().trim_matches('_');
-        if course.is_empty() {
-            return Course::None;
+}
+
+impl Course {
+    /// get the special course keyword of a token (`LZ-D` -> `LZ`)
+    fn special_keyword(token: &str) -> Option<&'static str> {
+        let token = token.trim().to_uppercase();
+        for keyword in ["LZ", "VBS", "BS", "UEMI", "SP_INT", "SPI", "AG"].iter() {
+            if token.starts_with(keyword)
+                && !token[keyword.len()..].starts_with(|v: char| v.is_ascii_alphabetic())
+            {
+                return Some(keyword);
+            }
         }
+        None
+    }
 
-        if class.is_empty() && class.contains("---") {
-            eprintln!(
-                "Error: DSB: Course: could not parse {} (empty string)",
-                class
-            );
-            return Course::None;
-        }
-        if class.to_lowercase().contains("klausur") {
-            eprintln!("Error: DSB: Course: could not parse exam: {{{}}}", course);
-            return Course::Sec2Exam {
-                track: 0,
-                name: String::new(),
-                kind: CourseKind::GK { number: 0 },
-            };
+    /// test if token is a special Sec 1 course (`LZ`, `BS`, `VBS`, `UEMI`, `SPI`, `AG`)
+    pub fn is_special_str(token: &str) -> bool {
+        Course::special_keyword(token).is_some()
+    }
+
+    /// parse special Sec 1 course from group (`5a`, `AG`) and subject (`LZ-D`, `AG-NW4`)
+    /// returns None if neither is a special course
+    pub fn from_special_str(group: &str, subject: &str) -> Option<Self> {
+        let group = group.trim();
+        let subject = subject.trim();
+        let keyword =
+            Course::special_keyword(subject).or_else(|| Course::special_keyword(group))?;
+
+        // strip the keyword of the subject (`AG-NW4` -> `NW4`)
+        let mut subject = subject.to_string();
+        if subject
+            .get(..keyword.len())
+            .map_or(false, |v| v.eq_ignore_ascii_case(keyword))
+        {
+            subject = subject[keyword.len()..]
+                .trim_start_matches(&['-', '_'][..])
+                .to_string();
         }
+        let group = group.to_string();
 
-        let sClass: u32 = (class.as_bytes()[0] as u32 - '0' as u32) as u32;
-        if sClass > 0 && sClass < 9 {
-            return Course::Sec1 {
-                name: course.to_string(),
-            };
+        Some(match keyword {
+            "LZ" => Course::LearningTime { group, subject },
+            "BS" | "VBS" | "UEMI" => Course::Supervision { group, subject },
+            _ => Course::WorkingGroup { group, subject },
+        })
+    }
+}
+
+impl fmt::Display for Course {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Course::None => Ok(()),
+            Course::Sec1 { name } => write!(f, "{}", name),
+            Course::Sec2 { name, kind, .. } => write!(f, "{} {}", name, kind),
+            Course::Sec2Exam { name, kind, .. } => write!(f, "Klausur {} {}", name, kind),
+            Course::LearningTime { group, subject } => {
+                write!(f, "{}", join(&["Lernzeit", subject, special_group(group)]))
+            }
+            Course::Supervision { group, subject } => {
+                write!(f, "{}", join(&["Aufsicht", group, subject]))
+            }
+            Course::WorkingGroup { group, subject } => {
+                write!(f, "{}", join(&["AG", subject, special_group(group)]))
+            }
         }
+    }
+}
 
-        let course: Vec<&str> = course.split("-").collect();
-        if course.len() != 2 {
-            eprintln!(
-                "Error: DSB: Course: could not parse {} (wrong number of arguments)",
-                course.connect(" ")
-            );
-            return Course::None;
-        }
-        let name = course[0].to_ascii_uppercase();
-        let kind: CourseKind = CourseKind::from_dsb_str(course[1]);
-
-        Course::Sec2 {
-            track: 0,
-            name,
-            kind,
-        }
-    }
-    /// parse planinfo teacher string
-    /// this function is indetend to be run after deciding that it is not a Sec 1 course
-    pub fn from_planinfo_teacher_str(course: &str, namein: &str) -> Self {
-        let course = course.trim();
-        let namein = namein.trim();
-        let kind = CourseKind::from_planinfo_teacher_str(namein);
-        let mut track: i16 = 0;
-        if let Some(nr) = course.as_bytes().last() {
-            track = (*nr as u32 - '0' as u32) as i16;
-        }
-        let mut name = String::new();
-        let data: Vec<&str> = namein.split("-").collect();
-        if let Some(data) = data.first() {
-            name = data.to_string() + " ";
-        }
-        let data: Vec<&str> = course.split("-").collect();
-        if let Some(data) = data.first() {
-            name += data;
-        }
-        Course::Sec2 { track, name, kind }
-    }
-    /// parse planinfo teacher string
-    /// this function is indetend to be run after deciding that it is not a Sec 1 course
-    pub fn from_planinfo_room_str(course: &str, namein: &str) -> Self {
-        let course = course.trim();
-        let namein = namein.trim();
-        let kind = CourseKind::from_planinfo_teacher_str(namein);
-        let track = 0;
-        let mut name = String::new();
-        let data: Vec<&str> = namein.split("-").collect();
-        if let Some(data) = data.first() {
-            name = data.to_string() + " ";
-        }
-        let data: Vec<&str> = course.split("-").collect();
-        if let Some(data) = data.first() {
-            name += data;
-        }
-        Course::Sec2 { track, name, kind }
-    }
-    /// parse planinfo teacher string
-    /// this function is indetend to be run after deciding that it is not a Sec 1 course
-    pub fn from_planinfo_students_str(course: &str, namein: &str) -> Self {
-        let course = course.trim();
-        let namein = namein.trim();
-        let kind = CourseKind::from_planinfo_teacher_str(namein);
-        let mut track: i16 = 0;
-        let course = course.trim_matches('(');
-        let course = course.trim_matches(')');
-        if let Some(nr) = course.as_bytes().last() {
-            track = (*nr as u32 - '0' as u32) as i16;
-        }
-
-        let namein: Vec<&str> = namein.split('-').collect();
-        let name = namein[0].to_string();
-
-        Course::Sec2 { track, name, kind }
+/// get group of special course, empty if it is only the keyword (`AG`)
+fn special_group(group: &str) -> &str {
+    if Course::is_special_str(group) {
+        ""
+    } else {
+        group
     }
 }
 
+/// join the not empty parts with spaces
+fn join(parts: &[&str]) -> String {
+    let parts: Vec<&str> = parts
+        .iter()
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+        .collect();
+    parts.join(" ")
+}
+
 impl Default for Course {
     fn default() -> Self {
         Course::None
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum CourseKind {
     None,
     GK { number: i16 },
@@ -342,44 +642,14 @@ impl CourseKind {
     pub fn new() -> Self {
         CourseKind::None
     }
-    /// parse dsb courseKind string
-    pub fn from_dsb_str(kind: &str) -> Self {
-        let kind = kind.trim();
-        if kind.is_empty() {
-            return CourseKind::None;
-        }
-        if kind.len() != 3 && kind.len() != 4 {
-            eprintln!(
-                "Error: DSB: CourseKind: kind string is not len 3 {{{}}}",
-                kind
-            );
-            return CourseKind::None;
-        }
-        //let number: i16 = (kind.as_bytes()[2] as u32 - '0' as u32) as i16;
-        let number: &str = &kind[2..kind.len()];
-        let number: i16 = number.parse().unwrap_or(0);
-        if kind.starts_with("GK") {
-            return CourseKind::GK { number };
-        } else if kind.starts_with("LK") {
-            return CourseKind::LK { number };
-        } else {
-            eprintln!("Error: DSB: CourseKind: error parsing {{{}}}", kind);
-            return CourseKind::None;
-        }
-    }
-    /// parse planinfo teacher str
-    pub fn from_planinfo_teacher_str(course: &str) -> Self {
-        let course = course.trim();
-        if course.is_empty() {
-            return CourseKind::None;
-        }
-        let mut number: i16 = 0;
-        if let Some(nr) = course.as_bytes().last() {
-            number = (*nr as u32 - '0' as u32) as i16;
-        }
-        if course.to_lowercase().contains("lk") {
-            return CourseKind::LK { number };
+}
+
+impl fmt::Display for CourseKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CourseKind::None => Ok(()),
+            CourseKind::GK { number } => write!(f, "GK{}", number),
+            CourseKind::LK { number } => write!(f, "LK{}", number),
         }
-        return CourseKind::GK { number };
     }
 }
@@ -0,0 +1,382 @@
+//! test file for the common types
+
+use super::{Course, CourseKind, Hour, Layout, RoomParser};
+
+mod special {
+    use super::*;
+
+    #[test]
+    fn learning_time() {
+        match Course::from_special_str("5a", "LZ-D") {
+            Some(Course::LearningTime { group, subject }) => {
+                assert_eq!(group, "5a");
+                assert_eq!(subject, "D");
+            }
+            course => panic!("wrong course {:?}", course),
+        }
+    }
+
+    #[test]
+    fn supervision() {
+        match Course::from_special_str("VBS", "") {
+            Some(Course::Supervision { group, subject }) => {
+                assert_eq!(group, "VBS");
+                assert_eq!(subject, "");
+            }
+            course => panic!("wrong course {:?}", course),
+        }
+        assert!(Course::is_special_str("UEMI"));
+    }
+
+    #[test]
+    fn working_group() {
+        match Course::from_special_str("AG", "AG-NW4") {
+            Some(Course::WorkingGroup { group, subject }) => {
+                assert_eq!(group, "AG");
+                assert_eq!(subject, "NW4");
+            }
+            course => panic!("wrong course {:?}", course),
+        }
+        match Course::from_special_str("SPI", "Sp_Int") {
+            Some(Course::WorkingGroup { group, subject }) => {
+                assert_eq!(group, "SPI");
+                assert_eq!(subject, "");
+            }
+            course => panic!("wrong course {:?}", course),
+        }
+    }
+
+    #[test]
+    fn non_ascii_subject() {
+        // `ſ` upper-cases to `S`, so `bſ` is a supervision but the keyword can not be stripped
+        match Course::from_special_str("5a", "bſ") {
+            Some(Course::Supervision { group, subject }) => {
+                assert_eq!(group, "5a");
+                assert_eq!(subject, "bſ");
+            }
+            course => panic!("wrong course {:?}", course),
+        }
+    }
+
+    #[test]
+    fn no_special() {
+        assert!(Course::from_special_str("5a", "D").is_none());
+        assert!(Course::from_special_str("Q1", "BI-GK1").is_none());
+        assert!(!Course::is_special_str("AGR"));
+    }
+
+    #[test]
+    fn display() {
+        let course = Course::from_special_str("AG", "AG-NW4").unwrap();
+        assert_eq!(course.to_string(), "AG NW4");
+        let course = Course::from_special_str("5a", "LZ").unwrap();
+        assert_eq!(course.to_string(), "Lernzeit 5a");
+    }
+
+    #[test]
+    fn planinfo_teacher() {
+        let mut hour = Hour::new();
+        hour.parse_planinfo_teacher("5a LZ-D A114", "MUE", &RoomParser::new());
+        match &hour.course {
+            Course::LearningTime { group, subject } => {
+                assert_eq!(group, "5a");
+                assert_eq!(subject, "D");
+            }
+            course => panic!("wrong course {:?}", course),
+        }
+        assert_eq!(hour.room.to_string(), "A114");
+        assert_eq!(hour.teacher.name, "MUE");
+
+        let mut hour = Hour::new();
+        hour.parse_planinfo_teacher("BS", "MUE", &RoomParser::new());
+        assert!(matches!(hour.course, Course::Supervision { .. }));
+        assert!(hour.room.is_none());
+    }
+
+    #[test]
+    fn planinfo_room() {
+        let mut hour = Hour::new();
+        hour.parse_planinfo_room("AG AG-NW4 KRD", "B127", &RoomParser::new());
+        match &hour.course {
+            Course::WorkingGroup { subject, .. } => assert_eq!(subject, "NW4"),
+            course => panic!("wrong course {:?}", course),
+        }
+        assert_eq!(hour.teacher.name, "KRD");
+        assert_eq!(hour.room.to_string(), "B127");
+    }
+
+    #[test]
+    fn dsb() {
+        assert!(matches!(
+            Course::parse(Layout::Dsb, "AG", "AG-NW4").unwrap(),
+            Course::WorkingGroup { .. }
+        ));
+        assert!(matches!(
+            Course::parse(Layout::Dsb, "BS", "BS").unwrap(),
+            Course::Supervision { .. }
+        ));
+    }
+}
+
+mod course {
+    use super::super::course::{Grade, Group, Spec};
+    use super::*;
+
+    fn sec2(name: &str, kind: CourseKind, track: i16) -> Course {
+        Course::Sec2 {
+            track,
+            name: name.to_string(),
+            kind,
+        }
+    }
+
+    fn sec1(name: &str) -> Course {
+        Course::Sec1 {
+            name: name.to_string(),
+        }
+    }
+
+    fn gk(number: i16) -> CourseKind {
+        CourseKind::GK { number }
+    }
+
+    fn lk(number: i16) -> CourseKind {
+        CourseKind::LK { number }
+    }
+
+    /// course strings of the dsb and planinfo fixtures with the expected course
+    #[test]
+    fn corpus() {
+        let corpus = [
+            // substitution plan, class and course
+            (Layout::Dsb, "Q1", "BI-GK4", sec2("BI", gk(4), 0)),
+            (Layout::Dsb, "Q1", "CH-LK1", sec2("CH", lk(1), 0)),
+            (Layout::Dsb, "EF", "F6-GK2", sec2("F6", gk(2), 0)),
+            (Layout::Dsb, "Q1", "M-GK9", sec2("M", gk(9), 0)),
+            (Layout::Dsb, "EF", "sp-lk1", sec2("SP", lk(1), 0)),
+            (Layout::Dsb, "5a", "D", sec1("D")),
+            (Layout::Dsb, "7ad", "KR-1", sec1("KR-1")),
+            (Layout::Dsb, "9bde", "F6-2", sec1("F6-2")),
+            (Layout::Dsb, "8e", "BIe", sec1("BIe")),
+            (
+                Layout::Dsb,
+                "10a",
+                "Sp_Int",
+                Course::WorkingGroup {
+                    group: "10a".to_string(),
+                    subject: String::new(),
+                },
+            ),
+            (Layout::Dsb, "Q1", "", Course::None),
+            (Layout::Dsb, "Q1", "___", Course::None),
+            // teacher and room plans, group and course
+            (Layout::Planinfo, "5a", "D", sec1("D 5a")),
+            (Layout::Planinfo, "Q1", "M-GK1", sec2("M Q1", gk(1), 0)),
+            (Layout::Planinfo, "Q1-G3", "PH-GK3", sec2("PH Q1", gk(3), 3)),
+            (Layout::Planinfo, "G8", "PL-GK1", sec2("PL", gk(1), 8)),
+            (Layout::Planinfo, "EF", "E-GK11", sec2("E EF", gk(11), 0)),
+            // student plans, track and course
+            (Layout::Students, "(G7)", "SWe-GK1", sec2("SWe", gk(1), 7)),
+            (Layout::Students, "(G4)", "R0-GK1", sec2("R0", gk(1), 4)),
+            (Layout::Students, "()", "KL-GK11", sec2("KL", gk(11), 0)),
+            (Layout::Students, "", "D-LK3", sec2("D", lk(3), 0)),
+        ];
+        for (layout, group, course, expected) in corpus.iter() {
+            assert_eq!(
+                &Course::parse(*layout, group, course).unwrap(),
+                expected,
+                "{:?} {} {}",
+                layout,
+                group,
+                course
+            );
+        }
+    }
+
+    #[test]
+    fn exam() {
+        assert_eq!(
+            Course::parse(Layout::Dsb, "Q1", "Klausur D-GK1").unwrap(),
+            Course::Sec2Exam {
+                track: 0,
+                name: "D".to_string(),
+                kind: gk(1),
+            }
+        );
+        assert_eq!(
+            Course::parse(Layout::Students, "Klausur (G2)", "IF-GK1").unwrap(),
+            Course::Sec2Exam {
+                track: 2,
+                name: "IF".to_string(),
+                kind: gk(1),
+            }
+        );
+        assert!(Course::parse(Layout::Dsb, "Klausur", "").is_err());
+    }
+
+    #[test]
+    fn errors() {
+        let corpus = [
+            (Layout::Dsb, "Q1", "BI-GK"),
+            (Layout::Dsb, "Q1", "BI-XK1"),
+            (Layout::Dsb, "Q1", "BI-GKx"),
+            (Layout::Dsb, "Q1", "M-GK1-FOM"),
+            (Layout::Dsb, "EF", "F6-1"),
+            (Layout::Dsb, "SN", "BIe"),
+            (Layout::Dsb, "Q1", "Start um 8.25 Uhr!"),
+            (Layout::Planinfo, "Ü1", "M-GK1"),
+            (Layout::Students, "(Gx)", "PH-GK3"),
+            (Layout::Students, "()", "-GK3"),
+        ];
+        for (layout, group, course) in corpus.iter() {
+            assert!(
+                Course::parse(*layout, group, course).is_err(),
+                "{:?} {} {}",
+                layout,
+                group,
+                course
+            );
+        }
+    }
+
+    #[test]
+    fn parts() {
+        assert_eq!(
+            Group::parse("Q2-G3").unwrap(),
+            Group {
+                grade: Some(Grade::Sec2("Q2".to_string())),
+                track: 3,
+            }
+        );
+        assert_eq!(Group::parse("()").unwrap(), Group::default());
+        assert_eq!(
+            Group::parse("7aef").unwrap().grade,
+            Some(Grade::Sec1("7aef".to_string()))
+        );
+        assert_eq!(
+            Spec::parse("F6-1").unwrap(),
+            Spec {
+                subject: "F6".to_string(),
+                kind: CourseKind::None,
+                number: Some(1),
+            }
+        );
+        assert_eq!(CourseKind::parse("lk12").unwrap(), lk(12));
+        assert!(CourseKind::parse("GK-1").is_err());
+    }
+
+    #[test]
+    fn planinfo_hours() {
+        let rooms = RoomParser::new();
+        let mut hour = Hour::new();
+        hour.parse_planinfo_room("Q1-G3 PH-GK3 KRD", "B127", &rooms);
+        assert_eq!(hour.course, sec2("PH Q1", gk(3), 3));
+
+        let mut hour = Hour::new();
+        hour.parse_planinfo_student("(G1) PH-GK3 KRD B127", "", &rooms);
+        assert_eq!(hour.course, sec2("PH", gk(3), 1));
+        assert_eq!(hour.teacher.name, "KRD");
+
+        let mut hour = Hour::new();
+        hour.parse_planinfo_teacher("?? M-GK1 A114", "FOM", &rooms);
+        assert_eq!(hour.course, Course::None);
+        assert_eq!(hour.room.to_string(), "A114");
+    }
+}
+
+mod room {
+    use super::super::{LegacyRoom, Room, RoomParser, RoomPattern};
+
+    fn parser() -> RoomParser {
+        let mut parser = RoomParser::new();
+        parser.patterns.push(RoomPattern {
+            pattern: String::from("TH*"),
+            building: String::from("Sport"),
+            name: Some(String::from("Turnhalle *")),
+        });
+        parser.patterns.push(RoomPattern {
+            pattern: String::from("Sportplatz"),
+            building: String::from("Sport"),
+            name: None,
+        });
+        parser
+            .aliases
+            .insert(String::from("Aula"), String::from("A001"));
+        parser
+    }
+
+    #[test]
+    fn default_parser() {
+        let room = Room::from_dsb_str("A114");
+        assert_eq!(room.building(), Some("A"));
+        assert_eq!(room.to_string(), "A114");
+        let room = Room::from_dsb_str("A10a");
+        assert_eq!(room.building(), Some("A"));
+        assert_eq!(room.id, "A10a");
+        let room = Room::from_dsb_str("TH1");
+        assert_eq!(room.building(), Some("TH"));
+        let room = Room::from_dsb_str("Sportplatz");
+        assert_eq!(room.building(), None);
+        assert_eq!(room.to_string(), "Sportplatz");
+        assert!(Room::from_dsb_str("---").is_none());
+        assert!(Room::from_dsb_str(" ").is_none());
+    }
+
+    #[test]
+    fn patterns_and_aliases() {
+        let parser = parser();
+        let room = parser.parse("th2");
+        assert_eq!(room.building, "Sport");
+        assert_eq!(room.id, "th2");
+        assert_eq!(room.name, Some(String::from("Turnhalle 2")));
+        // the pattern needs an identifier
+        assert_eq!(parser.parse("TH").building(), None);
+
+        // patterns without star match exactly
+        let room = parser.parse("sportplatz");
+        assert_eq!(room.building, "Sport");
+        assert_eq!(room.id, "sportplatz");
+        assert_eq!(room.name, None);
+        assert_eq!(parser.parse("Sportplatz2").building(), None);
+
+        let room = parser.parse("aula");
+        assert_eq!(room.id, "A001");
+        assert_eq!(room.building(), Some("A"));
+        assert_eq!(room.name, Some(String::from("Aula")));
+        assert!(room.same(&Room::from_dsb_str("a001")));
+    }
+
+    #[test]
+    fn is_room() {
+        let parser = parser();
+        assert!(parser.is_room("A114"));
+        assert!(parser.is_room("TH1"));
+        assert!(parser.is_room("Aula"));
+        assert!(parser.is_room("Sportplatz"));
+        assert!(!RoomParser::new().is_room("Sportplatz"));
+        assert!(!parser.is_room("AG-NW4"));
+        assert!(!parser.is_room("5a"));
+    }
+
+    #[test]
+    fn legacy() {
+        let room: Room = LegacyRoom::B { room: 12 }.into();
+        assert_eq!(room.to_string(), "B012");
+        assert_eq!(room.legacy(), Some(LegacyRoom::B { room: 12 }));
+        assert_eq!(Room::from(LegacyRoom::None), Room::new());
+        assert_eq!(Room::new().legacy(), Some(LegacyRoom::None));
+        assert_eq!(Room::from_dsb_str("A10a").legacy(), None);
+        assert_eq!(Room::from_dsb_str("TH1").legacy(), None);
+    }
+
+    #[test]
+    fn deserialize_legacy() {
+        let room: Room = serde_json::from_str(r#"{"A":{"room":114}}"#).unwrap();
+        assert_eq!(room, Room::from_dsb_str("A114"));
+        let room: Room = serde_json::from_str(r#""None""#).unwrap();
+        assert!(room.is_none());
+        let room: Room =
+            serde_json::from_str(r#"{"building":"Sport","id":"TH1","name":null}"#).unwrap();
+        assert_eq!(room.building, "Sport");
+    }
+}
@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::{Entry, EntryKind, Teacher};
+
+/// break a duty takes place in, Untis names breaks by the following period
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Slot {
+    /// period after the break, 1 is the time before the first period
+    pub before: i16,
+}
+
+impl Slot {
+    /// parse break slot of the period column
+    /// `3` and `vor 3` are the break before period 3, `2/3` the break between 2 and 3
+    /// and `nach 6` the time after period 6
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim().to_lowercase();
+        let before = if let Some(period) = input.strip_prefix("vor") {
+            period.trim().trim_end_matches('.').parse().ok()?
+        } else if let Some(period) = input.strip_prefix("nach") {
+            period.trim().trim_end_matches('.').parse::<i16>().ok()? + 1
+        } else if let Some((_, next)) = input.split_once('/') {
+            next.trim().parse().ok()?
+        } else {
+            input.trim_end_matches('.').parse().ok()?
+        };
+        if before < 1 {
+            return None;
+        }
+        Some(Self { before })
+    }
+}
+
+impl fmt::Display for Slot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.before - 1, self.before)
+    }
+}
+
+/// break supervision of a teacher plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Duty {
+    /// break of the duty
+    pub slot: Slot,
+
+    /// where to supervise (`Hof`, `Pausenhalle`), free text of the room column
+    pub location: String,
+
+    /// teacher the duty belongs to
+    pub teacher: Teacher,
+
+    /// teacher taking over the duty, None if the teacher does it
+    pub substitute: Option<Teacher>,
+
+    pub message: String,
+}
+
+impl Duty {
+    /// create duty of a teacher plan row, period is the text of the period column
+    /// None if the row is no break supervision or the period is no break
+    pub fn from_entry(entry: &Entry, period: &str) -> Option<Self> {
+        if entry.kind != EntryKind::BreakSupervision {
+            return None;
+        }
+        let slot = match Slot::parse(period) {
+            Some(slot) => slot,
+            None => {
+                eprintln!("Error: DSB: Duty: could not parse break {{{}}}", period);
+                return None;
+            }
+        };
+
+        let mut teacher = Teacher::new();
+        teacher.name = entry.name.trim().to_string();
+        let substitute = &entry.new_teacher;
+        let substitute =
+            if substitute.name.is_empty() || substitute.name.eq_ignore_ascii_case(&teacher.name) {
+                None
+            } else {
+                Some(substitute.clone())
+            };
+        Some(Self {
+            slot,
+            location: entry.room.to_string(),
+            teacher,
+            substitute,
+            message: entry.message.clone(),
+        })
+    }
+
+    /// teacher doing the duty, the substitute if there is one
+    pub fn on_duty(&self) -> &Teacher {
+        self.substitute.as_ref().unwrap_or(&self.teacher)
+    }
+
+    /// test if the duty concerns teacher (short name), as owner or substitute
+    pub fn concerns(&self, teacher: &str) -> bool {
+        self.teacher.name.eq_ignore_ascii_case(teacher)
+            || self
+                .substitute
+                .as_ref()
+                .map(|v| v.name.eq_ignore_ascii_case(teacher))
+                .unwrap_or(false)
+    }
+}
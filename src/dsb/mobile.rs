@@ -0,0 +1,162 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use super::Config;
+use super::Error;
+use super::Result;
+use crate::error::Context;
+
+/// zero token returned for wrong credentials
+const NO_TOKEN: &str = "00000000-0000-0000-0000-000000000000";
+
+/// news of dsbmobile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct News {
+    pub date: Option<NaiveDateTime>,
+    pub title: String,
+    pub detail: String,
+}
+
+/// client for the token based dsbmobile api
+pub struct Client {
+    client: reqwest::Client,
+    url: String,
+    token: String,
+}
+
+impl Client {
+    /// authenticate with user and password of conf
+    pub fn login(conf: &Config) -> Result<Self> {
+        let client = reqwest::Client::new();
+        let mut res = client
+            .get(&format!("{}/authid", conf.api_url))
+            .query(&[
+                ("bundleid", "de.heinekingmedia.dsbmobile"),
+                ("appversion", "35"),
+                ("osversion", "22"),
+                ("pushid", ""),
+                ("user", conf.user_id.as_str()),
+                ("password", conf.password.as_str()),
+            ])
+            .send()
+            .with_context(|| format!("GET {}/authid", conf.api_url))?;
+        if !res.status().is_success() {
+            return Err(Error::new_upstream_status(res.status().as_u16())
+                .context(format!("GET {}/authid", conf.api_url)));
+        }
+        let token = parse_token(&res.text()?)?;
+        if conf.verbose >= 3 {
+            println!("Debug3: DSB: got token");
+        }
+        Ok(Self {
+            client,
+            url: conf.api_url.clone(),
+            token,
+        })
+    }
+
+    /// get body of api endpoint
+    fn get(&self, endpoint: &str) -> Result<String> {
+        let mut res = self
+            .client
+            .get(&format!("{}/{}", self.url, endpoint))
+            .query(&[("authid", self.token.as_str())])
+            .send()
+            .with_context(|| format!("GET {}/{}", self.url, endpoint))?;
+        if !res.status().is_success() {
+            return Err(Error::new_upstream_status(res.status().as_u16())
+                .context(format!("GET {}/{}", self.url, endpoint)));
+        }
+        res.text()
+            .with_context(|| format!("GET {}/{}", self.url, endpoint))
+    }
+
+    /// get the urls of the pages of the timetable with title
+    pub fn timetables(&self, title: &str) -> Result<Vec<String>> {
+        parse_timetables(&self.get("dsbtimetables")?, title)
+    }
+
+    /// get the news
+    pub fn news(&self) -> Result<Vec<News>> {
+        parse_news(&self.get("newstab")?)
+    }
+}
+
+/// parse token of authid response (`"<uuid>"`)
+pub fn parse_token(body: &str) -> Result<String> {
+    let token: String = serde_json::from_str(body)?;
+    if token.is_empty() || token == NO_TOKEN {
+        return Err(Error::new_auth_failed(
+            "dsb: wrong user or password".to_string(),
+        ));
+    }
+    Ok(token)
+}
+
+/// parse timetable list and get the page urls of the timetable with title
+/// fails if no timetable has the title, another plan is never used instead
+pub fn parse_timetables(body: &str, title: &str) -> Result<Vec<String>> {
+    let json: serde_json::Value = serde_json::from_str(body)?;
+    let tables = match json.as_array() {
+        Some(tables) => tables,
+        None => return Err(Error::new_layout_changed("dsbtimetables".to_string())),
+    };
+    let titles: Vec<&str> = tables
+        .iter()
+        .filter_map(|v| v.get("Title").and_then(|v| v.as_str()))
+        .collect();
+    let table = match tables
+        .iter()
+        .find(|v| v.get("Title").and_then(|v| v.as_str()) == Some(title))
+    {
+        Some(table) => table,
+        None => {
+            return Err(Error::new_layout_changed(format!(
+                "dsbtimetables: no timetable {{{}}}, found {{{}}}",
+                title,
+                titles.join(", ")
+            )))
+        }
+    };
+
+    let mut ret = Vec::new();
+    match table.get("Childs").and_then(|v| v.as_array()) {
+        Some(childs) if !childs.is_empty() => {
+            for v in childs.iter() {
+                if let Some(url) = v.get("Detail").and_then(|v| v.as_str()) {
+                    ret.push(url.to_string());
+                }
+            }
+        }
+        _ => {
+            if let Some(url) = table.get("Detail").and_then(|v| v.as_str()) {
+                ret.push(url.to_string());
+            }
+        }
+    }
+    Ok(ret)
+}
+
+/// parse news list
+pub fn parse_news(body: &str) -> Result<Vec<News>> {
+    let json: serde_json::Value = serde_json::from_str(body)?;
+    let news = match json.as_array() {
+        Some(news) => news,
+        None => return Err(Error::new_layout_changed("newstab".to_string())),
+    };
+    let text = |v: &serde_json::Value, key: &str| -> String {
+        v.get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .trim()
+            .to_string()
+    };
+    Ok(news
+        .iter()
+        .map(|v| News {
+            date: NaiveDateTime::parse_from_str(&text(v, "Date"), "%d.%m.%Y %H:%M").ok(),
+            title: text(v, "Title"),
+            detail: text(v, "Detail"),
+        })
+        .collect())
+}
@@ -1,21 +1,55 @@
-use super::error::Error;
+use super::error::{Context, Error};
 use chrono::prelude::*;
 use serde_json::json;
 use std::clone::Clone;
+use std::collections::BTreeMap;
 use std::io::prelude::*;
-use std::thread;
 
 use html5ever::parse_document;
 use html5ever::rcdom::{Handle, Node, NodeData, RcDom};
 use html5ever::tendril::TendrilSink;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
+use super::calendar;
 #[doc(inline)]
 pub use super::error::Result;
-use super::storage::MongoDB;
+use super::source::{Sink, Source};
 
-pub use super::common::{Course, Room, Teacher};
+pub use super::common::{Course, Layout, Room, RoomParser, Teacher};
+
+/// client for the token based dsbmobile api
+pub mod mobile;
+
+/// page sequence of the Untis substitution monitor
+pub mod untis;
+
+/// break supervisions of the teacher plans
+pub mod duty;
+
+// tests as sub module
+#[cfg(test)] // only add when running tests
+mod test;
+
+/// way to get the dsb plans
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    /// emulate the web app, needs the cookie
+    Web,
+    /// token based api of the app, falls back to the web app on errors
+    Token,
+}
+
+impl Mode {
+    /// parse mode name (`web`, `token`)
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "web" => Some(Mode::Web),
+            "token" => Some(Mode::Token),
+            _ => None,
+        }
+    }
+}
 
 /// config struct for dsb informations
 #[derive(Clone)]
@@ -35,6 +69,28 @@ pub struct Config {
     /// only use when you use another host for dsb
     /// defaults to `https://www.dsbmobile.de/JsonHandlerWeb.ashx/GetData`
     pub url: String,
+
+    /// way to get the plans
+    pub mode: Mode,
+
+    /// url of the token based api
+    /// only use when you use another host for dsb
+    /// defaults to `https://mobileapi.dsbcontrol.de`
+    pub api_url: String,
+
+    /// title of the timetable in the token based api
+    pub timetable: String,
+
+    /// title of the timetable of the teachers in the token based api (`DSBLehrer`)
+    /// stored next to the plan of the students, empty to not load it
+    pub teacher_timetable: String,
+
+    /// parser for the rooms of the school
+    pub room_parser: RoomParser,
+
+    /// extra keywords of the kind column (`Sportvertretung` -> `Substitution`)
+    /// for schools with customized Untis labels
+    pub kinds: BTreeMap<String, EntryKind>,
 }
 
 impl Config {
@@ -46,31 +102,56 @@ impl Config {
             cookie: String::new(),
             verbose: 0,
             url: String::from("https://www.dsbmobile.de/JsonHandlerWeb.ashx/GetData"),
+            mode: Mode::Web,
+            api_url: String::from("https://mobileapi.dsbcontrol.de"),
+            timetable: String::from("DSBSchueler"),
+            teacher_timetable: String::new(),
+            room_parser: RoomParser::new(),
+            kinds: BTreeMap::new(),
         }
     }
 
-    /// start parser
-    pub fn run(&self, db: MongoDB) -> Result<()> {
-        let conf = self.clone();
-        thread::spawn(move || {
-            conf.run_int(db);
-        });
-        Ok(())
+    /// get dsb content
+    fn get(&self) -> Result<Vec<DSB>> {
+        if self.mode == Mode::Token {
+            match self.get_token() {
+                Ok(dsb) => return Ok(dsb),
+                Err(err) => eprintln!("Error: DSB: token api: {}, use web app", err),
+            }
+        }
+        self.get_web()
     }
 
-    /// internal run function holding the mail loop of the thread
-    fn run_int(self, db: MongoDB) {
-        loop {
-            let dsb = self.get().unwrap();
-            for v in dsb.iter() {
-                db.dsb_write(v).unwrap();
+    /// get dsb content with the token based api
+    fn get_token(&self) -> Result<Vec<DSB>> {
+        let client = mobile::Client::login(self)?;
+        let news = match client.news() {
+            Ok(news) => news,
+            Err(err) => {
+                eprintln!("Error: DSB: news: {}", err);
+                Vec::new()
             }
-            std::thread::sleep(std::time::Duration::from_secs(300)); //sleep 5 min
+        };
+
+        let mut ret = self.get_timetable(&client, &self.timetable)?;
+        for dsb in ret.iter_mut() {
+            dsb.news = news.clone();
         }
+        Ok(ret)
     }
 
-    /// get dsb content
-    fn get(&self) -> Result<Vec<DSB>> {
+    /// get the plans of the timetable with title of the token based api
+    fn get_timetable(&self, client: &mobile::Client, title: &str) -> Result<Vec<DSB>> {
+        let http = reqwest::Client::new();
+        let mut pages = Vec::new();
+        for url in client.timetables(title)?.iter() {
+            pages.append(&mut self.get_plan(&http, url)?);
+        }
+        Ok(DSB::merge(pages))
+    }
+
+    /// get dsb content with the web app
+    fn get_web(&self) -> Result<Vec<DSB>> {
         let data = self.gen_request_payload()?;
 
         let client = reqwest::Client::new();
@@ -82,19 +163,70 @@ impl Config {
             .header("X-Requested-With", "XMLHttpRequest")
             .header("Referer", "https://www.dsbmobile.de/default.aspx")
             .body(data)
-            .send()?;
+            .send()
+            .with_context(|| format!("POST {}", self.url))?;
 
-        let body = dsb.text().unwrap();
+        let body = dsb.text().with_context(|| format!("POST {}", self.url))?;
 
-        let url = self.decode_dsb_payload(&body).unwrap();
-        let mut html = client.get(&url)
+        let url = self.decode_dsb_payload(&body)?;
+        self.get_plan(&client, &url)
+    }
+
+    /// get and parse the plan starting at url
+    fn get_plan(&self, client: &reqwest::Client, url: &str) -> Result<Vec<DSB>> {
+        self.read_plan(url, |url| self.get_page(client, url))
+    }
+
+    /// read and parse the plan starting at url with the pages returned by fetch
+    /// the pages of a monitor are followed by their meta refresh or their number (`subst_002.htm`)
+    pub fn read_plan<F: FnMut(&str) -> Result<String>>(
+        &self,
+        url: &str,
+        mut fetch: F,
+    ) -> Result<Vec<DSB>> {
+        let mut pages = Vec::new();
+        let mut visited: Vec<String> = Vec::new();
+        let mut next = Some(url.to_string());
+        while let Some(url) = next.take() {
+            if visited.contains(&url) || visited.len() >= untis::MAX_PAGES {
+                break;
+            }
+            // the first page has to be a monitor, the following may be guessed
+            // and hosts often serve their not found page with status 200
+            let page = fetch(&url).and_then(|html| Ok((self.parse(&html)?, html)));
+            let (mut days, html) = match page {
+                Ok(page) => page,
+                Err(err) if !visited.is_empty() => {
+                    if self.verbose >= 2 {
+                        println!("Debug2: DSB: stop at page {}: {}", url, err);
+                    }
+                    break;
+                }
+                Err(err) => return Err(err),
+            };
+            pages.append(&mut days);
+            next = untis::refresh_url(&url, &html).or_else(|| untis::successor(&url));
+            visited.push(url);
+        }
+        if self.verbose >= 3 {
+            println!("Debug3: DSB: read {} pages", visited.len());
+        }
+        Ok(DSB::merge(pages))
+    }
+
+    /// get the html of a single page
+    fn get_page(&self, client: &reqwest::Client, url: &str) -> Result<String> {
+        let mut html = client.get(url)
             .header("Cookie", self.cookie.clone())
             .header("User-Agent", "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/62.0.3202.94 Safari/537.36")
             .header("Bundle_ID", "de.heinekingmedia.inhouse.dsbmobile.web")
             .header("Accept-Encoding", "latin1")
-            .send()?;
+            .send()
+            .with_context(|| format!("GET {}", url))?;
         if html.status().as_u16() != 200 {
-            return Err(Error::new_field_not_exists("not 200 foo".to_string()));
+            return Err(
+                Error::new_upstream_status(html.status().as_u16()).context(format!("GET {}", url))
+            );
         }
         for (h, v) in html.headers().iter() {
             if self.verbose >= 3 {
@@ -102,10 +234,7 @@ impl Config {
             }
         }
 
-        let html = html.text()?;
-        let dsb = self.parse(&html)?;
-
-        Ok(dsb) // change
+        Ok(html.text()?)
     }
 
     /// create request payload
@@ -149,12 +278,12 @@ impl Config {
 
         // check that d exists and is a string
         if json.get("d") == None {
-            return Err(super::error::Error::new_field_not_exists("d".to_string()));
+            return Err(super::error::Error::new_layout_changed("d".to_string()));
         }
         let d = json.get("d").unwrap();
 
         if !d.is_string() {
-            return Err(super::error::Error::new_field_not_exists("d".to_string()));
+            return Err(super::error::Error::new_layout_changed("d".to_string()));
         }
         let d = d.as_str().unwrap();
 
@@ -169,14 +298,14 @@ impl Config {
         let json: serde_json::Value = serde_json::from_str(&String::from_utf8_lossy(&data))?;
 
         if json.get("ResultMenuItems") == None {
-            return Err(super::error::Error::new_field_not_exists(
+            return Err(super::error::Error::new_layout_changed(
                 "data.ResultMenuItems".to_string(),
             ));
         }
         let json = json.get("ResultMenuItems").unwrap();
 
         if !json.is_array() {
-            return Err(super::error::Error::new_field_not_exists(
+            return Err(super::error::Error::new_layout_changed(
                 "data.ResultMenuItems".to_string(),
             ));
         }
@@ -186,14 +315,14 @@ impl Config {
         let mut index = 0;
         while !x {
             if json.get(index) == None {
-                return Err(super::error::Error::new_field_not_exists(format!(
+                return Err(super::error::Error::new_layout_changed(format!(
                     "data.ResultMenuItems.{}",
                     index
                 )));
             }
             let json = json.get(index).unwrap();
             if json.get("Title") == None {
-                return Err(super::error::Error::new_field_not_exists(format!(
+                return Err(super::error::Error::new_layout_changed(format!(
                     "data.ResultMenuItems.{}.Title",
                     index
                 )));
@@ -204,14 +333,14 @@ impl Config {
                     x = true;
 
                     if json.get("Childs") == None {
-                        return Err(super::error::Error::new_field_not_exists(format!(
+                        return Err(super::error::Error::new_layout_changed(format!(
                             "data.ResultMenuItems.{}.Childs",
                             index
                         )));
                     }
                     let json = json.get("Childs").unwrap();
                     if !json.is_array() {
-                        return Err(super::error::Error::new_field_not_exists(format!(
+                        return Err(super::error::Error::new_layout_changed(format!(
                             "data.ResultMenuItems.{}.Childs",
                             index
                         )));
@@ -223,14 +352,14 @@ impl Config {
                         indexy += 1;
                         let title = v.get("Title");
                         if title == None {
-                            return Err(super::error::Error::new_field_not_exists(format!(
+                            return Err(super::error::Error::new_layout_changed(format!(
                                 "data.ResultMenuItems.{}.Childs.{}.Title",
                                 index, indexy
                             )));
                         }
                         let title = title.unwrap();
                         if !title.is_string() {
-                            return Err(super::error::Error::new_field_not_exists(format!(
+                            return Err(super::error::Error::new_layout_changed(format!(
                                 "data.ResultMenuItems.{}.Childs.{}.Title",
                                 index, indexy
                             )));
@@ -239,7 +368,7 @@ impl Config {
                         if title == "Pläne" {
                             let v = v.get("Root");
                             if v == None {
-                                return Err(super::error::Error::new_field_not_exists(format!(
+                                return Err(super::error::Error::new_layout_changed(format!(
                                     "data.ResultMenuItems.{}.Childs.{}.Root",
                                     index, indexy
                                 )));
@@ -247,14 +376,14 @@ impl Config {
                             let v = v.unwrap();
                             let v = v.get("Childs");
                             if v == None {
-                                return Err(super::error::Error::new_field_not_exists(format!(
+                                return Err(super::error::Error::new_layout_changed(format!(
                                     "data.ResultMenuItems.{}.Childs.{}.Root.Childs",
                                     index, indexy
                                 )));
                             }
                             let v = v.unwrap();
                             if !v.is_array() {
-                                return Err(super::error::Error::new_field_not_exists(format!(
+                                return Err(super::error::Error::new_layout_changed(format!(
                                     "data.ResultMenuItems.{}.Childs.{}.Root.Childs",
                                     index, indexy
                                 )));
@@ -265,26 +394,18 @@ impl Config {
                                 indexz += 1;
                                 let title = v.get("Title");
                                 if title == None {
-                                    return Err(
-                                        super::error::Error::new_field_not_exists(
-                                            format!(
-                                                "data.ResultMenuItems.{}.Childs.{}.Root.Childs.{}.Title",
-                                                index, indexy, indexz
-                                            )
-                                        )
-                                    );
+                                    return Err(super::error::Error::new_layout_changed(format!(
+                                        "data.ResultMenuItems.{}.Childs.{}.Root.Childs.{}.Title",
+                                        index, indexy, indexz
+                                    )));
                                 }
                                 let title = title.unwrap();
                                 let title = title.as_str();
                                 if title == None {
-                                    return Err(
-                                        super::error::Error::new_field_not_exists(
-                                            format!(
-                                                "data.ResultMenuItems.{}.Childs.{}.Root.Childs.{}.Title",
-                                                index, indexy, indexz
-                                            )
-                                        )
-                                    );
+                                    return Err(super::error::Error::new_layout_changed(format!(
+                                        "data.ResultMenuItems.{}.Childs.{}.Root.Childs.{}.Title",
+                                        index, indexy, indexz
+                                    )));
                                 }
                                 if title.unwrap() == "DSBSchueler" {
                                     if let Some(childs) = v.get("Childs") {
@@ -302,7 +423,7 @@ impl Config {
                     }
                 }
             } else {
-                return Err(super::error::Error::new_field_not_exists(format!(
+                return Err(super::error::Error::new_layout_changed(format!(
                     "data.ResultMenuItems.{}",
                     index
                 )));
@@ -312,22 +433,18 @@ impl Config {
     }
 
     /// parse dsb content
-    fn parse(&self, html: &str) -> Result<Vec<DSB>> {
-        //let mut html = html.to_string();
+    /// pages without a `mon_head` table are no Untis monitor and return LayoutChanged
+    pub fn parse(&self, html: &str) -> Result<Vec<DSB>> {
         let html = html.replace("&nbsp;", " ");
         let dom = parse_document(RcDom::default(), Default::default())
             .from_utf8()
-            .read_from(&mut html.as_bytes())
-            .unwrap();
-        let dsb = self.parse_dom(&dom.document).unwrap();
-        Ok(dsb)
+            .read_from(&mut html.as_bytes())?;
+        self.parse_dom(&dom.document)
     }
 
     fn parse_dom(&self, handle: &Handle) -> Result<Vec<DSB>> {
         let mut dsb_return: Vec<DSB> = Vec::new();
-        let node: &Node = handle;
-        let nodeVec = node.children.borrow();
-        let node: &Node = &nodeVec[0];
+        let node = child(handle, 0, "html")?;
 
         for v in node.children.borrow().iter() {
             let v: &Node = v;
@@ -335,6 +452,8 @@ impl Config {
                 let name: &html5ever::QualName = name;
                 if name.local.to_string() == "body" {
                     let mut found_mod_head = false;
+                    // header of the page, copied for further days without own header
+                    let mut head: Option<DSB> = None;
                     for w in v.children.borrow().iter() {
                         let w: &Node = w;
                         if let NodeData::Element {
@@ -350,7 +469,9 @@ impl Config {
                                     if attr.name.local.to_string() == "class"
                                         && attr.value.to_string() == "mon_head"
                                     {
-                                        dsb_return.push(DSB::new_mon_head(w));
+                                        let dsb = DSB::new_mon_head(w)?;
+                                        head = Some(dsb.clone());
+                                        dsb_return.push(dsb);
 
                                         found_mod_head = true;
                                     }
@@ -358,9 +479,16 @@ impl Config {
                             } else if name.local.to_string() == "center" && found_mod_head {
                                 if let Some(dsb) = dsb_return.last_mut() {
                                     let dsb: &mut DSB = dsb;
-                                    self.parse_center(w, dsb);
+                                    self.parse_center(w, dsb)?;
                                 }
                                 found_mod_head = false;
+                            } else if name.local.to_string() == "center" && has_mon_title(w) {
+                                // several days on one page share the header
+                                if let Some(head) = &head {
+                                    let mut dsb = head.clone();
+                                    self.parse_center(w, &mut dsb)?;
+                                    dsb_return.push(dsb);
+                                }
                             }
                         }
                     }
@@ -368,33 +496,39 @@ impl Config {
             }
         }
 
+        if dsb_return.is_empty() {
+            return Err(Error::new_layout_changed("mon_head".to_string()));
+        }
         Ok(dsb_return)
     }
 
     /// parse dsb center
-    fn parse_center(&self, node: &Node, dsb: &mut DSB) {
-        let mon_title: &Node = &node.children.borrow()[1];
-        let mon_title: &Node = &mon_title.children.borrow()[0];
+    fn parse_center(&self, node: &Node, dsb: &mut DSB) -> Result<()> {
+        let mon_title = child(node, 1, "center.mon_title")?;
+        let mon_title = child(&mon_title, 0, "center.mon_title")?;
         if let NodeData::Text { ref contents } = mon_title.data {
             let contents = escape_default(&contents.borrow());
-            dsb.parse_mon_title(&contents);
+            dsb.parse_mon_title(&contents)?;
         }
 
-        let info: &Node = &node.children.borrow()[3];
-        dsb.parse_info_table(info);
+        let info = child(node, 3, "center.info")?;
+        dsb.parse_info_table(&info, &self.room_parser)?;
 
-        let rows: &Node = &node.children.borrow()[5];
-        let rows: &Node = &rows.children.borrow()[1];
-        let rows: &Node = &rows.children.borrow()[1];
+        let rows = child(node, 5, "center.rows")?;
+        let rows = child(&rows, 1, "center.rows")?;
+        let rows = child(&rows, 1, "center.rows")?;
+        // entry index and period of the break supervisions
+        let mut supervisions = Vec::new();
         for v in rows.children.borrow().iter() {
             let v: &Node = v;
             if v.children.borrow().len() == 8 {
-                let class: &Node = &v.children.borrow()[0];
-                let class: &Node = &class.children.borrow()[0];
+                let class = child(v, 0, "center.class")?;
+                let class = child(&class, 0, "center.class")?;
                 let mut new = false;
                 if let NodeData::Text { ref contents } = class.data {
                     let contents = escape_default(&contents.borrow());
-                    if contents.contains("Klasse") {
+                    // header of the class and the teacher plans
+                    if contents.contains("Klasse") || contents.contains("Lehrer") {
                         continue;
                     }
                     if contents != " " {
@@ -402,80 +536,92 @@ impl Config {
                         new = true;
                     }
                 }
-                let mut entrie: &mut Entry = dsb.entries.last_mut().unwrap();
+                // continuation rows need an entry before them
+                let entrie: &mut Entry = match dsb.entries.last_mut() {
+                    Some(entrie) => entrie,
+                    None => continue,
+                };
+                let mut period = String::new();
                 if new {
-                    let hour: &Node = &v.children.borrow()[1];
-                    let hour: &Node = &hour.children.borrow()[0];
-                    let hour: &Node = &hour.children.borrow()[0];
+                    let hour = child(v, 1, "center.hour")?;
+                    let hour = child(&hour, 0, "center.hour")?;
+                    let hour = child(&hour, 0, "center.hour")?;
                     if let NodeData::Text { ref contents } = hour.data {
                         let contents = escape_default(&contents.borrow());
-                        if contents.contains("-") {
-                            let contents: Vec<&str> = contents.split(" - ").collect();
-                            entrie.time.from = contents[0].parse().unwrap_or(0);
-                            entrie.time.to = contents[1].parse().unwrap_or(0);
-                        } else {
-                            let contents = contents.chars().next().unwrap();
-                            let t: i16 = (contents as u32 - '0' as u32) as i16;
+                        period = contents.clone();
+                        if contents.contains('-') {
+                            let mut bounds = contents.split('-').map(|v| v.trim().parse());
+                            match (bounds.next(), bounds.next(), bounds.next()) {
+                                (Some(Ok(from)), Some(Ok(to)), None) => {
+                                    entrie.time.from = from;
+                                    entrie.time.to = to;
+                                }
+                                _ => {
+                                    return Err(Error::new_layout_changed(
+                                        "center.hour".to_string(),
+                                    ))
+                                }
+                            }
+                        } else if let Ok(t) = contents.trim().parse() {
+                            // break slots like `vor 1` have no period
                             entrie.time.from = t;
                             entrie.time.to = t;
                         }
                     }
 
-                    let substitute: &Node = &v.children.borrow()[2];
-                    let substitute: &Node = &substitute.children.borrow()[0];
-                    let substitute: &Node = &substitute.children.borrow()[0];
+                    let substitute = child(v, 2, "center.substitute")?;
+                    let substitute = child(&substitute, 0, "center.substitute")?;
+                    let substitute = child(&substitute, 0, "center.substitute")?;
                     if let NodeData::Text { ref contents } = substitute.data {
                         let contents = escape_default(&contents.borrow());
                         let mut teacher: Teacher = Teacher::new();
                         teacher.name = contents.trim().trim_matches('-').to_string();
                         entrie.new_teacher = teacher;
                     }
-                    let course: &Node = &v.children.borrow()[3];
-                    let course: &Node = &course.children.borrow()[0];
+                    let course = child(v, 3, "center.course")?;
+                    let course = child(&course, 0, "center.course")?;
                     if let NodeData::Text { ref contents } = course.data {
                         let contents = escape_default(&contents.borrow());
-                        entrie.course =
-                            Course::from_dsb_str(&entrie.name, contents.trim().trim_matches('-'));
+                        entrie.course = parse_course(&entrie.name, &contents);
                     }
-                    let course: &Node = &v.children.borrow()[4];
-                    let course: &Node = &course.children.borrow()[0];
+                    let course = child(v, 4, "center.course")?;
+                    let course = child(&course, 0, "center.course")?;
                     if let NodeData::Text { ref contents } = course.data {
                         let contents = escape_default(&contents.borrow());
-                        entrie.old_course =
-                            Course::from_dsb_str(&entrie.name, contents.trim().trim_matches('-'));
+                        entrie.old_course = parse_course(&entrie.name, &contents);
                     }
-                    let message: &Node = &v.children.borrow()[5];
-                    let message: &Node = &message.children.borrow()[0];
+                    let message = child(v, 5, "center.message")?;
+                    let message = child(&message, 0, "center.message")?;
                     if let NodeData::Text { ref contents } = message.data {
                         entrie.message = escape_default(&contents.borrow())
                             .trim()
                             .trim_matches('-')
                             .to_string();
                     }
-                    let kind: &Node = &v.children.borrow()[6];
-                    let kind: &Node = &kind.children.borrow()[0];
+                    let kind = child(v, 6, "center.kind")?;
+                    let kind = child(&kind, 0, "center.kind")?;
                     if let NodeData::Text { ref contents } = kind.data {
-                        let kind = escape_default(&contents.borrow())
-                            .trim()
-                            .trim_matches('-')
-                            .to_string();
-                        entrie.kind = EntryKind::parse_from_str(&kind);
+                        let kind = contents.borrow().trim().trim_matches('-').to_string();
+                        entrie.kind = EntryKind::parse(&kind, &self.kinds);
                     }
-                    let room: &Node = &v.children.borrow()[7];
-                    let room: &Node = &room.children.borrow()[0];
-                    let room: &Node = &room.children.borrow()[0];
+                    let room = child(v, 7, "center.room")?;
+                    let room = child(&room, 0, "center.room")?;
+                    let room = child(&room, 0, "center.room")?;
                     if let NodeData::Text { ref contents } = room.data {
                         let room: String = escape_default(&contents.borrow())
                             .trim()
                             .trim_matches('-')
                             .to_string();
                         if !room.is_empty() {
-                            entrie.room = Room::from_dsb_str(&room);
+                            entrie.room = self.room_parser.parse(&room);
                         }
                     }
+                    if entrie.kind == EntryKind::BreakSupervision {
+                        supervisions.push((dsb.entries.len() - 1, period));
+                    }
                 } else {
-                    let message: &Node = &v.children.borrow()[5];
-                    let message: &Node = &message.children.borrow()[0];
+                    let message = child(v, 5, "center.message")?;
+                    let message = child(&message, 0, "center.message")?;
                     if let NodeData::Text { ref contents } = message.data {
                         let message: String = escape_default(&contents.borrow())
                             .trim()
@@ -488,11 +634,80 @@ impl Config {
                 // header
             }
         }
+        // supervisions are duties and no substitutions, read them after their continuation rows
+        for (index, period) in supervisions.iter() {
+            if let Some(duty) = duty::Duty::from_entry(&dsb.entries[*index], period) {
+                dsb.duties.push(duty);
+            }
+        }
+        dsb.entries
+            .retain(|v| v.kind != EntryKind::BreakSupervision);
+        Ok(())
+    }
+}
+
+impl Source for Config {
+    fn name(&self) -> &str {
+        "DSB"
+    }
+
+    fn interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(300) // poll every 5 min
+    }
+
+    /// plans are published the day before, so also poll the day before school
+    fn is_due(&self, calendar: &calendar::Config, date: NaiveDate) -> bool {
+        calendar.is_school_day(date) || calendar.is_school_day(date.succ())
+    }
+
+    fn poll(&mut self, sink: &mut Sink) -> Result<()> {
+        for day in self.get()? {
+            sink.day(day)?;
+        }
+        Ok(())
+    }
+}
+
+/// source of the teacher plans, only available with the token based api
+#[derive(Clone)]
+pub struct TeacherPlan {
+    config: Config,
+}
+
+impl TeacherPlan {
+    /// create source of the teacher timetable of config
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+}
+
+impl Source for TeacherPlan {
+    fn name(&self) -> &str {
+        "DSB teachers"
+    }
+
+    fn interval(&self) -> std::time::Duration {
+        self.config.interval()
+    }
+
+    fn is_due(&self, calendar: &calendar::Config, date: NaiveDate) -> bool {
+        self.config.is_due(calendar, date)
+    }
+
+    fn poll(&mut self, sink: &mut Sink) -> Result<()> {
+        let client = mobile::Client::login(&self.config)?;
+        for day in self
+            .config
+            .get_timetable(&client, &self.config.teacher_timetable)?
+        {
+            sink.teacher_day(day)?;
+        }
+        Ok(())
     }
 }
 
 /// enum for A and B week
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Week {
     A,
     B,
@@ -501,21 +716,38 @@ pub enum Week {
 
 impl Week {
     /// parse char to week
-    fn parse(input: char) -> Self {
+    pub fn parse(input: char) -> Self {
         match input {
             'A' => return Week::A,
             'B' => return Week::B,
             _ => return Week::NoWeek(input),
         }
     }
+
+    /// test if week is an A or B week
+    pub fn is_ab(&self) -> bool {
+        match self {
+            Week::A | Week::B => true,
+            Week::NoWeek(_) => false,
+        }
+    }
+
+    /// get the following week type
+    pub fn other(&self) -> Self {
+        match self {
+            Week::A => Week::B,
+            Week::B => Week::A,
+            Week::NoWeek(c) => Week::NoWeek(*c),
+        }
+    }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Class {
     pub name: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DSB {
     /// school name
     pub school: String,
@@ -536,19 +768,33 @@ pub struct DSB {
     pub week: Week,
 
     /// Free lessons for everyone
-    pub FreeLessons: Option<String>,
+    pub free_lessons: Option<Duration>,
 
     /// teachers registerd not there
-    pub missing_teachers: Vec<Teacher>,
+    pub missing_teachers: Vec<MissingTeacher>,
+
+    /// classes registered not there
+    pub missing_classes: Vec<MissingClass>,
 
     /// rooms registered blocked
-    pub blocked_rooms: Vec<Room>,
+    pub blocked_rooms: Vec<BlockedRoom>,
 
     /// classes affected at this day
     pub affected_classes: Vec<Class>,
 
+    /// free text messages of the day
+    pub messages: Vec<String>,
+
+    /// news of dsbmobile when the plan was loaded
+    #[serde(default)]
+    pub news: Vec<mobile::News>,
+
     /// entries in this day
     pub entries: Vec<Entry>,
+
+    /// break supervisions of the teacher plans
+    #[serde(default)]
+    pub duties: Vec<duty::Duty>,
 }
 
 impl DSB {
@@ -560,30 +806,66 @@ impl DSB {
             updated_at: NaiveDateTime::from_timestamp(0, 0),
             date: NaiveDate::from_ymd(1970, 1, 1),
             week: Week::A,
-            FreeLessons: None,
+            free_lessons: None,
             missing_teachers: Vec::new(),
+            missing_classes: Vec::new(),
             blocked_rooms: Vec::new(),
             affected_classes: Vec::new(),
+            messages: Vec::new(),
+            news: Vec::new(),
             entries: Vec::new(),
+            duties: Vec::new(),
         }
     }
 
+    /// merge the days of several pages, days with the same date become one
+    /// the order of the first appearance of a date is kept
+    pub fn merge(pages: Vec<DSB>) -> Vec<DSB> {
+        let mut ret: Vec<DSB> = Vec::new();
+        for page in pages {
+            match ret.iter_mut().find(|v| v.date == page.date) {
+                Some(day) => day.append(page),
+                None => ret.push(page),
+            }
+        }
+        ret
+    }
+
+    /// add the content of another page of the same day
+    fn append(&mut self, mut other: DSB) {
+        if other.updated_at > self.updated_at {
+            self.updated_at = other.updated_at;
+        }
+        if self.free_lessons.is_none() {
+            self.free_lessons = other.free_lessons;
+        }
+        // the info table is repeated on every page
+        append_unique(&mut self.missing_teachers, other.missing_teachers);
+        append_unique(&mut self.missing_classes, other.missing_classes);
+        append_unique(&mut self.blocked_rooms, other.blocked_rooms);
+        append_unique(&mut self.affected_classes, other.affected_classes);
+        append_unique(&mut self.messages, other.messages);
+        append_unique(&mut self.news, other.news);
+        self.entries.append(&mut other.entries);
+        self.duties.append(&mut other.duties);
+    }
+
     /// create new instance from mon_head table dom tree
-    fn new_mon_head(handle: &Node) -> Self {
+    fn new_mon_head(handle: &Node) -> Result<Self> {
         let mut dsb: DSB = DSB::new();
         let node: &Node = handle;
-        let node: &Node = &node.children.borrow()[1];
-        let node: &Node = &node.children.borrow()[0];
-        let node: &Node = &node.children.borrow()[5];
-        let node: &Node = &node.children.borrow()[1];
-        let schule: &Node = &node.children.borrow()[0];
+        let node = child(node, 1, "mon_head")?;
+        let node = child(&node, 0, "mon_head")?;
+        let node = child(&node, 5, "mon_head")?;
+        let node = child(&node, 1, "mon_head")?;
+        let schule = child(&node, 0, "mon_head.school")?;
 
         if let NodeData::Text { ref contents } = schule.data {
             dsb.school = escape_default(&contents.borrow());
         }
         drop(schule);
 
-        let year: &Node = &node.children.borrow()[4];
+        let year = child(&node, 4, "mon_head.year")?;
         if let NodeData::Text { ref contents } = year.data {
             let year = escape_default(&contents.borrow());
             let year: Vec<&str> = year.split(" ").collect();
@@ -593,7 +875,7 @@ impl DSB {
         }
         drop(year);
 
-        let date: &Node = &node.children.borrow()[6];
+        let date = child(&node, 6, "mon_head.date")?;
 
         if let NodeData::Text { ref contents } = date.data {
             let date = escape_default(&contents.borrow());
@@ -605,7 +887,7 @@ impl DSB {
             }
         }
 
-        let date: &Node = &node.children.borrow()[8];
+        let date = child(&node, 8, "mon_head.date")?;
 
         if let NodeData::Text { ref contents } = date.data {
             let date = escape_default(&contents.borrow());
@@ -619,80 +901,225 @@ impl DSB {
                 .unwrap_or(NaiveDateTime::from_timestamp(0, 0));
             }
         }
-        dsb
+        Ok(dsb)
     }
 
     /// parse mon_title string to DSB info
     fn parse_mon_title(&mut self, info: &str) -> Result<()> {
-        self.week = Week::parse(info.as_bytes()[info.len() - 1] as char);
-        let strs = info.split_ascii_whitespace().collect::<Vec<&str>>();
-        self.date = chrono::NaiveDate::parse_from_str(strs[0], "%d.%m.%Y").unwrap(); //FIXME: unwrap
+        // the title may end with the page (`5.7.2019 Freitag, Woche B (Seite 1 / 2)`)
+        let week = match info.find("Woche ") {
+            Some(pos) => info[pos + "Woche ".len()..].chars().next(),
+            None => info.trim_end().chars().last(),
+        };
+        if let Some(week) = week {
+            self.week = Week::parse(week);
+        }
+        let date = info.split_ascii_whitespace().next().unwrap_or("");
+        self.date = match NaiveDate::parse_from_str(date, "%d.%m.%Y") {
+            Ok(date) => date,
+            Err(_) => {
+                return Err(Error::new_layout_changed(format!(
+                    "mon_title date {{{}}}",
+                    info
+                )))
+            }
+        };
         Ok(())
     }
 
     /// parse info table
-    fn parse_info_table(&mut self, node: &Node) {
-        let node: &Node = &node.children.borrow()[1];
+    fn parse_info_table(&mut self, node: &Node, rooms: &RoomParser) -> Result<()> {
+        let node = child(node, 1, "info.node")?;
 
         for v in node.children.borrow().iter() {
             let v: &Node = v;
-            if v.children.borrow().len() != 2 {
+            if v.children.borrow().len() == 1 {
+                // rows with one cell are the header (th) or a free text message (td)
+                let message = child(v, 0, "info.message")?;
+                if let NodeData::Element { ref name, .. } = message.data {
+                    let name: &html5ever::QualName = name;
+                    if name.local.to_string() == "td" {
+                        let message = escape_default(&node_text(&message));
+                        let message = message.trim();
+                        if !message.is_empty() {
+                            self.messages.push(message.to_string());
+                        }
+                    }
+                }
+                continue;
+            } else if v.children.borrow().len() != 2 {
                 continue;
             } else {
-                let infoType: &Node = &v.children.borrow()[0];
-                let infoType: &Node = &infoType.children.borrow()[0];
-                let mut infoString = String::new();
-                if let NodeData::Text { ref contents } = infoType.data {
-                    infoString = escape_default(&contents.borrow());
-                }
-                let infoString = infoString.trim();
-
-                let content: &Node = &v.children.borrow()[1];
-                let content: &Node = &content.children.borrow()[0];
-                let mut contentString = String::new();
-                if let NodeData::Text { ref contents } = content.data {
-                    contentString = escape_default(&contents.borrow());
-                }
-                let contentString = contentString.trim();
-
-                if infoString.to_lowercase() == "abwesende lehrer" {
-                    let contentString: Vec<&str> = contentString.split(", ").collect();
-                    for v in contentString.iter() {
-                        let v: &str = v.trim();
-                        let v: Vec<&str> = v.split(" ").collect();
-                        if v.len() != 1 {
-                            eprintln!("Error: DSB: unimplemented: td.info.{{Abwesende Lehrer}}.len {{{}}} {:?}", v.len(), v);
-                        }
-                        self.missing_teachers.push(Teacher {
-                            name: v[0].to_string(),
+                let info_type = child(v, 0, "info.info_type")?;
+                let info_string = escape_default(&node_text(&info_type));
+                let info_string = info_string.trim();
+
+                let content = child(v, 1, "info.content")?;
+                let content_string = escape_default(&node_text(&content));
+                let content_string = content_string.trim();
+
+                let info = info_string.to_lowercase();
+                if info == "abwesende lehrer" {
+                    for (name, time) in parse_info_list(content_string) {
+                        self.missing_teachers.push(MissingTeacher {
+                            teacher: Teacher {
+                                name,
+                                details: None,
+                            },
+                            time,
+                        });
+                    }
+                } else if info == "abwesende klassen" {
+                    for (name, time) in parse_info_list(content_string) {
+                        self.missing_classes.push(MissingClass {
+                            class: Class { name },
+                            time,
                         });
                     }
-                } else if infoString.to_lowercase() == "betroffene klassen" {
-                    let contentString: Vec<&str> = contentString.split(", ").collect();
-                    for v in contentString.iter() {
+                } else if info.starts_with("blockierte r") {
+                    // the umlaut is escaped (`R\u{fffd}ume`)
+                    for (name, time) in parse_info_list(content_string) {
+                        self.blocked_rooms.push(BlockedRoom {
+                            room: rooms.parse(&name),
+                            time,
+                        });
+                    }
+                } else if info == "betroffene klassen" {
+                    let content_string: Vec<&str> = content_string.split(", ").collect();
+                    for v in content_string.iter() {
                         let v: &str = v.trim();
                         self.affected_classes.push(Class {
                             name: v.to_string(),
                         });
                     }
+                } else if info.starts_with("unterrichtsfrei") {
+                    let time = content_string.trim_end_matches('.').trim_end_matches("Std");
+                    self.free_lessons = Duration::parse_from_str(time);
+                    if self.free_lessons.is_none() {
+                        eprintln!(
+                            "Error: DSB: could not parse free lessons {{{}}}",
+                            content_string
+                        );
+                        self.messages
+                            .push(format!("{}: {}", info_string, content_string));
+                    }
                 } else {
-                    eprintln!(
-                        "Error: DSB: unimplemented: td.info.{{{}}} {{{}}}",
-                        infoString, contentString
-                    );
+                    // keep unknown rows as message, so no information is lost
+                    self.messages
+                        .push(format!("{}: {}", info_string, content_string));
                 }
             }
         }
+        Ok(())
     }
 }
 
-pub struct Hour {
-    pub string: String,
-    pub start: chrono::DateTime<Utc>,
-    pub duration: chrono::Duration,
+/// teacher missing at this day, if `time` is `None` for the whole day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MissingTeacher {
+    pub teacher: Teacher,
+    pub time: Option<Duration>,
+}
+
+/// class missing at this day, if `time` is `None` for the whole day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MissingClass {
+    pub class: Class,
+    pub time: Option<Duration>,
 }
 
-#[derive(Debug, Serialize)]
+/// room blocked at this day, if `time` is `None` for the whole day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockedRoom {
+    pub room: Room,
+    pub time: Option<Duration>,
+}
+
+/// child of node at index, LayoutChanged with path if the page has no such child
+fn child(node: &Node, index: usize, path: &str) -> Result<Handle> {
+    match node.children.borrow().get(index) {
+        Some(child) => Ok(child.clone()),
+        None => Err(Error::new_layout_changed(path.to_string())),
+    }
+}
+
+/// append the items of other missing in list
+fn append_unique<T: PartialEq>(list: &mut Vec<T>, other: Vec<T>) {
+    for v in other {
+        if !list.contains(&v) {
+            list.push(v);
+        }
+    }
+}
+
+/// test if node has a `<div class="mon_title">` child
+fn has_mon_title(node: &Node) -> bool {
+    node.children.borrow().iter().any(|v| {
+        if let NodeData::Element {
+            ref name,
+            ref attrs,
+            ..
+        } = v.data
+        {
+            name.local.to_string() == "div"
+                && attrs.borrow().iter().any(|attr| {
+                    attr.name.local.to_string() == "class" && attr.value.to_string() == "mon_title"
+                })
+        } else {
+            false
+        }
+    })
+}
+
+/// parse course cell of class, logs courses not matching the course grammar
+fn parse_course(class: &str, input: &str) -> Course {
+    match Course::parse(Layout::Dsb, class, input.trim().trim_matches('-')) {
+        Ok(course) => course,
+        Err(err) => {
+            eprintln!("Error: DSB: Course: {}", err);
+            Course::None
+        }
+    }
+}
+
+/// parse info list like `ALEP, DAN (1-6), KAHN (4-4)`
+fn parse_info_list(input: &str) -> Vec<(String, Option<Duration>)> {
+    let mut ret = Vec::new();
+    for v in input.split(',') {
+        let v: &str = v.trim();
+        if v.is_empty() {
+            continue;
+        }
+        let v: Vec<&str> = v.splitn(2, '(').collect();
+        let name = v[0].trim().to_string();
+        let mut time = None;
+        if let Some(duration) = v.get(1) {
+            time = Duration::parse_from_str(duration);
+            if time.is_none() {
+                eprintln!(
+                    "Error: DSB: info list: could not parse time of {} {{{}}}",
+                    name, duration
+                );
+            }
+        }
+        ret.push((name, time));
+    }
+    ret
+}
+
+/// collect all text inside of node
+pub fn node_text(node: &Node) -> String {
+    let mut ret = String::new();
+    if let NodeData::Text { ref contents } = node.data {
+        ret += &contents.borrow();
+    }
+    for v in node.children.borrow().iter() {
+        ret += &node_text(v);
+    }
+    ret
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Duration {
     pub from: i16,
     pub to: i16,
@@ -702,43 +1129,139 @@ impl Duration {
     pub fn new() -> Self {
         Self { from: 0, to: 0 }
     }
+
+    /// parse period range like `3`, `1-6` or `(1 - 6)`
+    pub fn parse_from_str(input: &str) -> Option<Self> {
+        let input = input.trim().trim_start_matches('(').trim_end_matches(')');
+        let mut input = input.splitn(2, '-');
+        let from: i16 = input.next()?.trim().parse().ok()?;
+        let to: i16 = match input.next() {
+            Some(to) => to.trim().parse().ok()?,
+            None => from,
+        };
+        Some(Self { from, to })
+    }
+
+    /// test if period is in the duration
+    pub fn contains(&self, period: i16) -> bool {
+        self.from <= period && period <= self.to
+    }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum EntryKind {
     Unknow(String),
+    /// `Vertretung`, `Vtr.`
     Substitution,
+    /// `Entfall`
     Dropped,
+    /// `Sondereinsatz`
     Special,
+    /// `Unterricht geändert`
     Changed,
+    /// `Raumänderung`
     Room,
+    /// `Raum-Vtr.`, substitution in another room
+    RoomSubstitution,
+    /// `Verlegung`, lesson moved to another time
+    Moved,
+    /// `Tausch`, lessons swapped
+    Swap,
+    /// `Klausur`
+    Exam,
+    /// `Entfall für Lehrer`, the teacher is free but the class has a lesson
+    DroppedForTeacher,
+    /// `Statt-Vertretung`, substitution instead of the own lesson
+    InsteadOf,
+    /// `Freisetzung`, the teacher is released
+    Release,
+    /// `Pausenaufsicht`
+    BreakSupervision,
+    /// `Betreuung`
+    Care,
 }
 
+/// default vocabulary of the Untis kinds, the first keyword found in the kind wins
+/// more specific keywords have to come first (`Raum-Vtr.` before `Vtr.`)
+const ENTRY_KINDS: &[(&str, EntryKind)] = &[
+    ("raum-vtr", EntryKind::RoomSubstitution),
+    ("raumvertretung", EntryKind::RoomSubstitution),
+    ("statt-vertretung", EntryKind::InsteadOf),
+    ("statt-vtr", EntryKind::InsteadOf),
+    ("entfall für lehrer", EntryKind::DroppedForTeacher),
+    ("pausenaufsicht", EntryKind::BreakSupervision),
+    ("freisetzung", EntryKind::Release),
+    ("verlegung", EntryKind::Moved),
+    ("tausch", EntryKind::Swap),
+    ("klausur", EntryKind::Exam),
+    ("betreuung", EntryKind::Care),
+    ("sondereins", EntryKind::Special),
+    ("vertr", EntryKind::Substitution),
+    ("vtr", EntryKind::Substitution),
+    ("entfall", EntryKind::Dropped),
+    ("entfällt", EntryKind::Dropped),
+    ("geändert", EntryKind::Changed),
+    ("raum", EntryKind::Room),
+];
+
 impl EntryKind {
     pub fn new() -> Self {
         EntryKind::Unknow(String::new())
     }
-    fn parse_from_str(input: &str) -> Self {
-        if input.to_lowercase().contains("vertr") {
-            return EntryKind::Substitution;
-        } else if input.to_lowercase().contains("entf\\u{fffd}lllt") {
-            return EntryKind::Dropped;
-        } else if input.to_lowercase().contains("sondereins") {
-            return EntryKind::Special;
-        } else if input.to_lowercase().contains("ge\\u{fffd}ndert") {
-            return EntryKind::Changed;
-        } else if input.to_lowercase().contains("betreuung") {
-            return EntryKind::Special;
-        } else if input.to_lowercase().contains("raum") {
-            return EntryKind::Room;
-        } else {
-            eprintln!("Error: DBS: EntryKind: could not parse {}", input);
-            return EntryKind::Unknow(input.to_string());
+
+    /// parse kind column, the keywords of extra are checked before the default vocabulary
+    /// longer keywords of extra first
+    pub fn parse(input: &str, extra: &BTreeMap<String, EntryKind>) -> Self {
+        let folded = fold_kind(input);
+        let mut keywords: Vec<(&String, &EntryKind)> = extra.iter().collect();
+        keywords.sort_by_key(|(keyword, _)| std::cmp::Reverse(keyword.len()));
+        for (keyword, kind) in keywords {
+            let keyword = fold_kind(keyword);
+            if !keyword.is_empty() && folded.contains(&keyword) {
+                return kind.clone();
+            }
+        }
+        for (keyword, kind) in ENTRY_KINDS.iter() {
+            if folded.contains(&fold_kind(keyword)) {
+                return kind.clone();
+            }
+        }
+        eprintln!("Error: DBS: EntryKind: could not parse {}", input);
+        EntryKind::Unknow(input.to_string())
+    }
+}
+
+/// drop the extra keywords folding to nothing, they would match every kind
+pub fn valid_kinds(kinds: BTreeMap<String, EntryKind>) -> BTreeMap<String, EntryKind> {
+    kinds
+        .into_iter()
+        .filter(|(keyword, _)| {
+            let valid = !fold_kind(keyword).is_empty();
+            if !valid {
+                eprintln!(
+                    "Error: DSB: EntryKind: ignore keyword {{{}}} without ascii letters",
+                    keyword
+                );
+            }
+            valid
+        })
+        .collect()
+}
+
+/// fold kind for comparison, lower case, umlauts and broken characters of latin1 pages dropped
+/// and repeated characters collapsed
+/// (`Entfällt`, `Entf\u{fffd}llt` and the typo `Entfälllt` of the monitors are all `entflt`)
+fn fold_kind(input: &str) -> String {
+    let mut ret = String::new();
+    for v in input.trim().to_lowercase().chars().filter(|v| v.is_ascii()) {
+        if !ret.ends_with(v) {
+            ret.push(v);
         }
     }
+    ret
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Entry {
     pub name: String,
     pub course: Course,
@@ -763,7 +1286,7 @@ impl Entry {
             old_teacher: Teacher::new(),
             message: String::new(),
             kind: EntryKind::new(),
-            room: Room::None,
+            room: Room::new(),
         }
     }
 
@@ -0,0 +1,504 @@
+//! test file for the dsb parser
+
+/// parse the dsb.html example, the file is latin1 encoded
+fn parse_example() -> Vec<super::DSB> {
+    let html = String::from_utf8_lossy(include_bytes!("dsb.html"));
+    super::Config::new().parse(&html).unwrap()
+}
+
+mod duration {
+    use super::super::Duration;
+
+    #[test]
+    fn single() {
+        assert_eq!(
+            Duration::parse_from_str("3"),
+            Some(Duration { from: 3, to: 3 })
+        );
+    }
+
+    #[test]
+    fn range() {
+        assert_eq!(
+            Duration::parse_from_str("(1 - 6)"),
+            Some(Duration { from: 1, to: 6 })
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(Duration::parse_from_str("foo"), None);
+    }
+}
+
+mod info_table {
+    use super::super::Duration;
+    use super::parse_example;
+
+    #[test]
+    fn missing_teachers() {
+        let dsb = parse_example();
+        let dsb = &dsb[0];
+        assert_eq!(dsb.missing_teachers.len(), 31);
+        let dan = dsb
+            .missing_teachers
+            .iter()
+            .find(|v| v.teacher.name == "DAN")
+            .unwrap();
+        assert_eq!(dan.time, Some(Duration { from: 1, to: 6 }));
+        assert_eq!(dsb.missing_teachers[0].time, None);
+    }
+
+    #[test]
+    fn blocked_rooms() {
+        let dsb = parse_example();
+        let dsb = &dsb[0];
+        assert_eq!(dsb.blocked_rooms.len(), 3);
+        assert_eq!(dsb.blocked_rooms[0].time, None);
+        assert_eq!(dsb.blocked_rooms[2].time, Some(Duration { from: 3, to: 4 }));
+    }
+
+    #[test]
+    fn affected_classes() {
+        let dsb = parse_example();
+        assert_eq!(dsb[1].affected_classes.len(), 1);
+        assert_eq!(dsb[1].affected_classes[0].name, "EF");
+        assert!(dsb[1].messages.is_empty());
+    }
+}
+
+mod mobile {
+    use super::super::mobile::{parse_news, parse_timetables, parse_token};
+    use crate::error::ErrorKind;
+
+    #[test]
+    fn token() {
+        assert_eq!(
+            parse_token("\"3f9a1c2e-0b1d-4c8e-9a7f-1234567890ab\"").unwrap(),
+            "3f9a1c2e-0b1d-4c8e-9a7f-1234567890ab"
+        );
+        assert_eq!(
+            parse_token("\"00000000-0000-0000-0000-000000000000\"")
+                .unwrap_err()
+                .kind(),
+            ErrorKind::AuthFailed(String::from("dsb: wrong user or password"))
+        );
+        assert!(parse_token("\"\"").is_err());
+    }
+
+    #[test]
+    fn timetables() {
+        let body = r#"[
+            {"Id":"a","Title":"DSBLehrer","Detail":"","Childs":[
+                {"Id":"a1","Title":"Lehrer","Detail":"https://example.org/lehrer.htm"}
+            ]},
+            {"Id":"b","Title":"DSBSchueler","Detail":"","Childs":[
+                {"Id":"b1","Title":"heute","Detail":"https://example.org/1/subst_001.htm"},
+                {"Id":"b2","Title":"morgen","Detail":"https://example.org/2/subst_001.htm"}
+            ]}
+        ]"#;
+        assert_eq!(
+            parse_timetables(body, "DSBSchueler").unwrap(),
+            vec![
+                "https://example.org/1/subst_001.htm",
+                "https://example.org/2/subst_001.htm"
+            ]
+        );
+        // never fall back to another timetable
+        assert_eq!(
+            parse_timetables(body, "DSBSchüler").unwrap_err().kind(),
+            ErrorKind::LayoutChanged(String::from(
+                "dsbtimetables: no timetable {DSBSchüler}, found {DSBLehrer, DSBSchueler}"
+            ))
+        );
+        assert!(parse_timetables("[]", "DSBSchueler").is_err());
+        assert!(parse_timetables("{}", "DSBSchueler").is_err());
+    }
+
+    #[test]
+    fn news() {
+        let body = r#"[
+            {"Id":"n","Date":"18.10.2026 07:30","Title":"Elternsprechtag ","Detail":"Am Freitag"},
+            {"Id":"m","Title":"ohne Datum"}
+        ]"#;
+        let news = parse_news(body).unwrap();
+        assert_eq!(news.len(), 2);
+        assert_eq!(
+            news[0].date,
+            Some(chrono::NaiveDate::from_ymd(2026, 10, 18).and_hms(7, 30, 0))
+        );
+        assert_eq!(news[0].title, "Elternsprechtag");
+        assert_eq!(news[0].detail, "Am Freitag");
+        assert_eq!(news[1].date, None);
+        assert_eq!(news[1].detail, "");
+    }
+}
+
+mod untis {
+    use super::super::untis::{refresh_url, successor};
+    use super::super::{Config, Week, DSB};
+    use super::parse_example;
+    use crate::error::ErrorKind;
+
+    /// html of the example up to the first day
+    fn head() -> String {
+        let html = String::from_utf8_lossy(include_bytes!("dsb.html")).to_string();
+        html[..html.find("<center>").unwrap()].to_string()
+    }
+
+    /// html of the nth day of the example
+    fn day(n: usize) -> String {
+        let html = String::from_utf8_lossy(include_bytes!("dsb.html")).to_string();
+        let title = html
+            .match_indices("<div class=\"mon_title\">")
+            .nth(n)
+            .unwrap()
+            .0;
+        let start = html[..title].rfind("<center>").unwrap();
+        let end = start + html[start..].find("</font></center>").unwrap();
+        format!("{}</font></center>\n", &html[start..end])
+    }
+
+    #[test]
+    fn several_dates_on_page() {
+        let html = format!("{}{}{}</body></html>", head(), day(0), day(1));
+        let dsb = Config::new().parse(&html).unwrap();
+        assert_eq!(dsb.len(), 2);
+        assert_eq!(dsb[0].date, chrono::NaiveDate::from_ymd(2019, 7, 5));
+        assert_eq!(dsb[1].date, chrono::NaiveDate::from_ymd(2019, 7, 8));
+        assert_eq!(dsb[1].week, Week::A);
+        assert_eq!(dsb[1].school, dsb[0].school);
+        assert_eq!(dsb[1].updated_at, dsb[0].updated_at);
+        assert_eq!(dsb[1].entries.len(), 1);
+    }
+
+    #[test]
+    fn page_in_title() {
+        let html = format!("{}{}</body></html>", head(), day(0)).replace(
+            "Freitag, Woche B</div>",
+            "Freitag, Woche B (Seite 1 / 2)</div>",
+        );
+        let dsb = Config::new().parse(&html).unwrap();
+        assert_eq!(dsb[0].week, Week::B);
+        assert_eq!(dsb[0].date, chrono::NaiveDate::from_ymd(2019, 7, 5));
+    }
+
+    #[test]
+    fn not_untis() {
+        let html = "<html><head><title>Not Found</title></head><body><h1>404</h1></body></html>";
+        let err = Config::new().parse(html).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            ErrorKind::LayoutChanged(String::from("mon_head"))
+        );
+    }
+
+    #[test]
+    fn title_without_date() {
+        let html =
+            format!("{}{}</body></html>", head(), day(0)).replace("5.7.2019 Freitag", "Freitag");
+        let err = Config::new().parse(&html).unwrap_err();
+        match err.kind() {
+            ErrorKind::LayoutChanged(path) => assert!(path.starts_with("mon_title date")),
+            kind => panic!("unexpected kind {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn missing_rows() {
+        let day = day(0);
+        let table = day.find("<table class=\"mon_list\"").unwrap();
+        let html = format!("{}{}</center></body></html>", head(), &day[..table]);
+        let err = Config::new().parse(&html).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            ErrorKind::LayoutChanged(String::from("center.rows"))
+        );
+    }
+
+    #[test]
+    fn periods() {
+        let html = String::from_utf8_lossy(include_bytes!("dsb.html"))
+            .replacen(
+                "<b>3 - 4</b></td><td class=\"list\" align=\"center\"><b>SNEI</b>",
+                "<b>3-4</b></td><td class=\"list\" align=\"center\"><b>SNEI</b>",
+                1,
+            )
+            .replacen(
+                "<b>1 - 2</b></td><td class=\"list\" align=\"center\"><b>BLE</b>",
+                "<b>10</b></td><td class=\"list\" align=\"center\"><b>BLE</b>",
+                1,
+            );
+        let days = Config::new().parse(&html).unwrap();
+        let entry = |teacher: &str| {
+            days.iter()
+                .flat_map(|v| v.entries.iter())
+                .find(|v| v.new_teacher.name == teacher)
+                .unwrap()
+                .time
+                .clone()
+        };
+        assert_eq!((entry("SNEI").from, entry("SNEI").to), (3, 4));
+        assert_eq!((entry("BLE").from, entry("BLE").to), (10, 10));
+
+        let html = html.replacen("<b>3-4</b>", "<b>3-</b>", 1);
+        let err = Config::new().parse(&html).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            ErrorKind::LayoutChanged(String::from("center.hour"))
+        );
+    }
+
+    #[test]
+    fn successor_not_a_monitor() {
+        let plan = String::from_utf8_lossy(include_bytes!("dsb.html")).to_string();
+        let mut fetched = Vec::new();
+        let dsb = Config::new()
+            .read_plan("http://example.org/plan/subst_001.htm", |url| {
+                fetched.push(url.to_string());
+                match url {
+                    "http://example.org/plan/subst_001.htm" => Ok(plan.clone()),
+                    _ => Ok(String::from(
+                        "<html><body><h1>Seite nicht gefunden</h1></body></html>",
+                    )),
+                }
+            })
+            .unwrap();
+        assert_eq!(dsb, parse_example());
+        assert_eq!(
+            fetched,
+            vec![
+                "http://example.org/plan/subst_001.htm",
+                "http://example.org/plan/subst_002.htm",
+            ]
+        );
+    }
+
+    #[test]
+    fn first_page_not_a_monitor() {
+        let err = Config::new()
+            .read_plan("http://example.org/plan/subst_001.htm", |_| {
+                Ok(String::from("<html><body></body></html>"))
+            })
+            .unwrap_err();
+        assert_eq!(
+            err.kind(),
+            ErrorKind::LayoutChanged(String::from("mon_head"))
+        );
+    }
+
+    #[test]
+    fn merge_pages() {
+        let day = parse_example().remove(0);
+        let mut first = day.clone();
+        let mut second = day.clone();
+        second.entries = first.entries.split_off(10);
+        second.updated_at = day.updated_at + chrono::Duration::minutes(1);
+        let other = parse_example().remove(1);
+
+        let merged = DSB::merge(vec![first, other.clone(), second]);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].entries, day.entries);
+        assert_eq!(merged[0].missing_teachers, day.missing_teachers);
+        assert_eq!(merged[0].blocked_rooms, day.blocked_rooms);
+        assert_eq!(
+            merged[0].updated_at,
+            day.updated_at + chrono::Duration::minutes(1)
+        );
+        assert_eq!(merged[1], other);
+    }
+
+    #[test]
+    fn refresh() {
+        let html = r#"<html><head><meta http-equiv="Content-Type" content="text/html;">
+            <meta http-equiv="refresh" content="8; URL=subst_002.htm"></head></html>"#;
+        assert_eq!(
+            refresh_url("http://example.org/plan/subst_001.htm", html),
+            Some(String::from("http://example.org/plan/subst_002.htm"))
+        );
+        let html = "<META CONTENT='5;url=../other/subst_001.htm' HTTP-EQUIV='Refresh'>";
+        assert_eq!(
+            refresh_url("http://example.org/plan/subst_003.htm", html),
+            Some(String::from("http://example.org/other/subst_001.htm"))
+        );
+        let html = r#"<meta http-equiv="refresh" content="60">"#;
+        assert_eq!(refresh_url("http://example.org/", html), None);
+        assert_eq!(refresh_url("http://example.org/", "<html></html>"), None);
+    }
+
+    #[test]
+    fn numbered_pages() {
+        assert_eq!(
+            successor("http://example.org/f1/subst_001.htm"),
+            Some(String::from("http://example.org/f1/subst_002.htm"))
+        );
+        assert_eq!(
+            successor("subst_009.htm"),
+            Some(String::from("subst_010.htm"))
+        );
+        assert_eq!(successor("http://example.org/plan.htm"), None);
+    }
+}
+
+mod entry_kind {
+    use super::super::{valid_kinds, Config, EntryKind};
+    use super::parse_example;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn vocabulary() {
+        let extra = BTreeMap::new();
+        let kinds = [
+            ("Vertretung", EntryKind::Substitution),
+            ("Vtr.", EntryKind::Substitution),
+            ("Raum-Vtr.", EntryKind::RoomSubstitution),
+            ("Statt-Vertretung", EntryKind::InsteadOf),
+            ("Verlegung", EntryKind::Moved),
+            ("Tausch", EntryKind::Swap),
+            ("Klausur", EntryKind::Exam),
+            ("Entfall für Lehrer", EntryKind::DroppedForTeacher),
+            ("Entfall f\u{fffd}r Lehrer", EntryKind::DroppedForTeacher),
+            ("Entfall", EntryKind::Dropped),
+            ("Bereitschaft entf\u{fffd}llt", EntryKind::Dropped),
+            ("entfälllt", EntryKind::Dropped),
+            ("Freisetzung", EntryKind::Release),
+            ("Pausenaufsicht", EntryKind::BreakSupervision),
+            ("Betreuung", EntryKind::Care),
+            ("Sondereinsatz", EntryKind::Special),
+            ("Unterricht geändert", EntryKind::Changed),
+            ("Raumänderung", EntryKind::Room),
+        ];
+        for (input, kind) in kinds.iter() {
+            assert_eq!(&EntryKind::parse(input, &extra), kind, "{}", input);
+        }
+        assert_eq!(
+            EntryKind::parse("Start um 8.25 Uhr!", &extra),
+            EntryKind::Unknow(String::from("Start um 8.25 Uhr!"))
+        );
+    }
+
+    #[test]
+    fn extra_keywords() {
+        let mut extra = BTreeMap::new();
+        extra.insert(String::from("Sportvertretung"), EntryKind::Care);
+        extra.insert(String::from("Vertretung"), EntryKind::Special);
+        extra.insert(String::from("Mitbetreuung"), EntryKind::Substitution);
+        // the longest keyword wins over shorter ones and the defaults
+        assert_eq!(EntryKind::parse("Sportvertretung", &extra), EntryKind::Care);
+        assert_eq!(EntryKind::parse("Vertretung", &extra), EntryKind::Special);
+        assert_eq!(
+            EntryKind::parse("Mitbetreuung", &extra),
+            EntryKind::Substitution
+        );
+        assert_eq!(EntryKind::parse("Tausch", &extra), EntryKind::Swap);
+    }
+
+    #[test]
+    fn empty_keywords() {
+        let mut extra = BTreeMap::new();
+        extra.insert(String::from("Ä"), EntryKind::Care);
+        extra.insert(String::from(" "), EntryKind::Swap);
+        extra.insert(String::from("Aufsicht"), EntryKind::BreakSupervision);
+        // keywords folding to nothing never match
+        assert_eq!(EntryKind::parse("Entfall", &extra), EntryKind::Dropped);
+
+        let kinds = valid_kinds(extra);
+        assert_eq!(kinds.len(), 1);
+        assert_eq!(kinds["Aufsicht"], EntryKind::BreakSupervision);
+    }
+
+    #[test]
+    fn config_toml() {
+        let kinds: BTreeMap<String, EntryKind> = toml::from_str(
+            "\"Sportvertretung\" = \"Substitution\"\nAufsicht = \"BreakSupervision\"",
+        )
+        .unwrap();
+        assert_eq!(kinds["Aufsicht"], EntryKind::BreakSupervision);
+
+        let mut config = Config::new();
+        config.kinds = kinds;
+        assert_eq!(
+            EntryKind::parse("Aufsicht Hof", &config.kinds),
+            EntryKind::BreakSupervision
+        );
+    }
+
+    #[test]
+    fn example() {
+        let days = parse_example();
+        let unknown: Vec<&EntryKind> = days
+            .iter()
+            .flat_map(|v| v.entries.iter())
+            .map(|v| &v.kind)
+            .filter(|v| matches!(v, EntryKind::Unknow(_)))
+            .collect();
+        assert!(unknown.is_empty(), "{:?}", unknown);
+    }
+}
+
+mod duty {
+    use super::super::duty::{Duty, Slot};
+    use super::super::{Config, Entry, EntryKind};
+
+    #[test]
+    fn slot() {
+        assert_eq!(Slot::parse("3"), Some(Slot { before: 3 }));
+        assert_eq!(Slot::parse("vor 1"), Some(Slot { before: 1 }));
+        assert_eq!(Slot::parse("Vor 3."), Some(Slot { before: 3 }));
+        assert_eq!(Slot::parse("2/3"), Some(Slot { before: 3 }));
+        assert_eq!(Slot::parse("nach 6"), Some(Slot { before: 7 }));
+        assert_eq!(Slot::parse("0"), None);
+        assert_eq!(Slot::parse("Hof"), None);
+        assert_eq!(Slot { before: 3 }.to_string(), "2/3");
+    }
+
+    #[test]
+    fn from_entry() {
+        let mut entry = Entry::new_from_str("MUE");
+        entry.kind = EntryKind::BreakSupervision;
+        entry.new_teacher.name = String::from("OTT");
+        let duty = Duty::from_entry(&entry, "vor 3").unwrap();
+        assert_eq!(duty.slot, Slot { before: 3 });
+        assert_eq!(duty.teacher.name, "MUE");
+        assert_eq!(duty.on_duty().name, "OTT");
+        assert!(duty.concerns("ott"));
+        assert!(duty.concerns("MUE"));
+        assert!(!duty.concerns("BERG"));
+
+        entry.new_teacher.name = String::from("MUE");
+        let duty = Duty::from_entry(&entry, "3").unwrap();
+        assert_eq!(duty.substitute, None);
+        assert_eq!(duty.on_duty().name, "MUE");
+
+        assert!(Duty::from_entry(&entry, "Hof").is_none());
+        entry.kind = EntryKind::Substitution;
+        assert!(Duty::from_entry(&entry, "3").is_none());
+    }
+
+    #[test]
+    fn teacher_plan() {
+        let html = String::from_utf8_lossy(include_bytes!("dsb.html")).replace(
+            r#"<td class="list" align="center">5a</td><td class="list" align="center"><b>3 - 4</b></td><td class="list" align="center"><b>SNEI</b></td><td class="list" align="center">SN</td><td class="list" align="center">SN</td><td class="list">&nbsp;</td><td class="list" align="center">Betreuung</td><td class="list" align="center"><b>BAD</b></td>"#,
+            r#"<td class="list" align="center">MUE</td><td class="list" align="center"><b>vor 3</b></td><td class="list" align="center"><b>OTT</b></td><td class="list" align="center">&nbsp;</td><td class="list" align="center">&nbsp;</td><td class="list">&nbsp;</td><td class="list" align="center">Pausenaufsicht</td><td class="list" align="center"><b>Hof</b></td>"#,
+        );
+        let days = Config::new().parse(&html).unwrap();
+        let duties: Vec<&Duty> = days.iter().flat_map(|v| v.duties.iter()).collect();
+        assert_eq!(duties.len(), 1);
+        // the supervision is no substitution entry
+        let plain = String::from_utf8_lossy(include_bytes!("dsb.html"));
+        let entries =
+            |days: &[super::super::DSB]| days.iter().map(|v| v.entries.len()).sum::<usize>();
+        assert_eq!(
+            entries(&days) + 1,
+            entries(&Config::new().parse(&plain).unwrap())
+        );
+        assert!(days
+            .iter()
+            .flat_map(|v| v.entries.iter())
+            .all(|v| v.kind != EntryKind::BreakSupervision));
+        assert_eq!(duties[0].slot, Slot { before: 3 });
+        assert_eq!(duties[0].location, "Hof");
+        assert_eq!(duties[0].teacher.name, "MUE");
+        assert_eq!(duties[0].on_duty().name, "OTT");
+    }
+}
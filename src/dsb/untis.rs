@@ -0,0 +1,73 @@
+/// max pages of one monitor, guards against pages linking in a circle
+pub const MAX_PAGES: usize = 50;
+
+/// get the url of the meta refresh of a page (`<meta http-equiv="refresh" content="8; URL=subst_002.htm">`)
+/// relative urls are resolved against base
+pub fn refresh_url(base: &str, html: &str) -> Option<String> {
+    // ascii lowercase keeps the byte positions of html
+    let lower = html.to_ascii_lowercase();
+    let mut pos = 0;
+    while let Some(start) = lower[pos..].find("<meta") {
+        let start = pos + start;
+        let end = match lower[start..].find('>') {
+            Some(end) => start + end,
+            None => return None,
+        };
+        pos = end;
+        let tag = &lower[start..end];
+        if !tag.contains("refresh") {
+            continue;
+        }
+        let content = match tag.find("content=") {
+            Some(content) => start + content + "content=".len(),
+            None => continue,
+        };
+        let content = html[content..end].trim_start();
+        let content = match content.chars().next() {
+            Some(quote) if quote == '"' || quote == '\'' => {
+                let content = &content[1..];
+                &content[..content.find(quote).unwrap_or(content.len())]
+            }
+            _ => content.split_whitespace().next().unwrap_or(""),
+        };
+        let url = match content.to_ascii_lowercase().find("url=") {
+            Some(url) => content[url + "url=".len()..].trim(),
+            None => continue,
+        };
+        let url = url.trim_matches(&['"', '\''][..]);
+        if url.is_empty() {
+            return None;
+        }
+        return join(base, url);
+    }
+    None
+}
+
+/// get the url of the next numbered page (`subst_001.htm` -> `subst_002.htm`)
+pub fn successor(url: &str) -> Option<String> {
+    let name_start = url.rfind('/').map(|v| v + 1).unwrap_or(0);
+    let name = &url[name_start..];
+    let dot = name.rfind('.')?;
+    let stem = &name[..dot];
+    let digits = stem.len() - stem.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    if digits == 0 {
+        return None;
+    }
+    let number: usize = stem[stem.len() - digits..].parse().ok()?;
+    Some(format!(
+        "{}{}{:0width$}{}",
+        &url[..name_start],
+        &stem[..stem.len() - digits],
+        number + 1,
+        &name[dot..],
+        width = digits
+    ))
+}
+
+/// resolve url relative to base
+fn join(base: &str, url: &str) -> Option<String> {
+    match url::Url::parse(base) {
+        Ok(base) => base.join(url).ok().map(|v| v.to_string()),
+        Err(_) => Some(url.to_string()),
+    }
+}
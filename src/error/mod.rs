@@ -9,15 +9,42 @@ mod test;
 /// public type for Result predifined with `error::Error` as Error type
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// underlying error an Error was caused by
+pub type Source = Box<dyn std::error::Error + Send + Sync + 'static>;
+
 /// Error object used for Return values
+/// holds the kind, the error it was caused by and the context it happened in
 pub struct Error {
     my_kind: ErrorKind,
+
+    /// error causing this one, returned by `std::error::Error::source`
+    source: Option<Source>,
+
+    /// what was done when the error happened, innermost first (`GET https://...`, `dbidx 42`)
+    context: Vec<String>,
 }
 
 impl Error {
     /// creates a new Error
     pub fn new(kind: ErrorKind) -> Self {
-        Error { my_kind: kind }
+        Error {
+            my_kind: kind,
+            source: None,
+            context: Vec::new(),
+        }
+    }
+
+    /// creates a new Error caused by source
+    pub fn with_source<E: Into<Source>>(kind: ErrorKind, source: E) -> Self {
+        let mut err = Self::new(kind);
+        err.source = Some(source.into());
+        err
+    }
+
+    /// add context to the error, like the url, dbidx, date or field path it happened at
+    pub fn context<C: fmt::Display>(mut self, context: C) -> Self {
+        self.context.push(context.to_string());
+        self
     }
 
     /// returns the internal type of the error
@@ -25,6 +52,11 @@ impl Error {
         self.my_kind.clone()
     }
 
+    /// returns the context of the error, innermost first
+    pub fn contexts(&self) -> &[String] {
+        &self.context
+    }
+
     /// creates a new error of the type NoVersionSupplied
     pub fn new_no_version_supplied() -> Self {
         Self::new(ErrorKind::NoVersionSupplied)
@@ -35,6 +67,45 @@ impl Error {
         Self::new(ErrorKind::FieldNotExists(field))
     }
 
+    /// creates a new error of the Kind SchemaNotSupported
+    pub fn new_schema_not_supported(version: i32) -> Self {
+        Self::new(ErrorKind::SchemaNotSupported(version))
+    }
+
+    /// creates a new error of the Kind CourseNotParsable
+    pub fn new_course_not_parsable(input: String) -> Self {
+        Self::new(ErrorKind::CourseNotParsable(input))
+    }
+
+    /// creates a new error of the Kind AuthFailed
+    pub fn new_auth_failed(reason: String) -> Self {
+        Self::new(ErrorKind::AuthFailed(reason))
+    }
+
+    /// creates a new error of the Kind AuthExpired
+    pub fn new_auth_expired(reason: String) -> Self {
+        Self::new(ErrorKind::AuthExpired(reason))
+    }
+
+    /// creates a new error for an error status of the upstream
+    /// `429 Too Many Requests` is of the Kind RateLimited, every other of the Kind UpstreamStatus
+    pub fn new_upstream_status(status: u16) -> Self {
+        match status {
+            429 => Self::new(ErrorKind::RateLimited),
+            _ => Self::new(ErrorKind::UpstreamStatus(status)),
+        }
+    }
+
+    /// creates a new error of the Kind LayoutChanged
+    pub fn new_layout_changed(path: String) -> Self {
+        Self::new(ErrorKind::LayoutChanged(path))
+    }
+
+    /// creates a new error of the Kind EmptyPlan
+    pub fn new_empty_plan(plan: String) -> Self {
+        Self::new(ErrorKind::EmptyPlan(plan))
+    }
+
     /// converts a `std::io::ErrorKind` to an own ErrorKind enum
     fn io_to_kind(kind: io::ErrorKind) -> ErrorKind {
         match kind {
@@ -61,11 +132,41 @@ impl Error {
     }
 }
 
+/// add context to the error of a result
+/// ```ignore
+/// let body = client.get(url).send().with_context(|| format!("GET {}", url))?;
+/// ```
+pub trait Context<T> {
+    /// add context to the error
+    fn context<C: fmt::Display>(self, context: C) -> Result<T>;
+
+    /// add context created by f to the error, f is only called on errors
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, f: F) -> Result<T>;
+}
+
+impl<T, E: Into<Error>> Context<T> for std::result::Result<T, E> {
+    fn context<C: fmt::Display>(self, context: C) -> Result<T> {
+        self.map_err(|err| err.into().context(context))
+    }
+
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, f: F) -> Result<T> {
+        self.map_err(|err| err.into().context(f()))
+    }
+}
+
 /// implement std::fmt::Display to allow printing and to_string()
+/// prints the context, outermost first, the kind and the source
 impl fmt::Display for Error {
     /// standart formater for print! macro
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Error of Kind {}", self.my_kind.to_string()) // TODO: create a more readable output
+        for v in self.context.iter().rev() {
+            write!(f, "{}: ", v)?;
+        }
+        write!(f, "{}", self.my_kind.convert_to_string())?;
+        if let Some(source) = &self.source {
+            write!(f, ": {}", source)?;
+        }
+        Ok(())
     }
 }
 
@@ -73,7 +174,24 @@ impl fmt::Display for Error {
 impl fmt::Debug for Error {
     /// formater for `Debug` in print! macro
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "(Error of Kind {})", self.my_kind.to_string())
+        write!(f, "(Error of Kind {}", self.my_kind.convert_to_string())?;
+        if !self.context.is_empty() {
+            write!(f, ", context: {:?}", self.context)?;
+        }
+        if let Some(source) = &self.source {
+            write!(f, ", source: {:?}", source)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// implement std::error::Error to allow chaining with other errors
+impl std_error for Error {
+    fn source(&self) -> Option<&(dyn std_error + 'static)> {
+        match &self.source {
+            Some(source) => Some(source.as_ref()),
+            None => None,
+        }
     }
 }
 
@@ -81,68 +199,80 @@ impl fmt::Debug for Error {
 impl std::convert::From<std::io::Error> for Error {
     fn from(io_error: std::io::Error) -> Self {
         let kind = Error::io_to_kind(io_error.kind());
-        Error { my_kind: kind }
+        Self::with_source(kind, io_error)
     }
 }
 
 /// implement std::convert::From for std::io::ErrorKind
 impl std::convert::From<std::io::ErrorKind> for Error {
     fn from(io_kind: std::io::ErrorKind) -> Self {
-        let kind = Error::io_to_kind(io_kind);
-        Error { my_kind: kind }
+        Self::new(Error::io_to_kind(io_kind))
     }
 }
 
 /// implement std::convert::From for string
 impl std::convert::From<std::string::String> for Error {
     fn from(string: String) -> Self {
-        Error {
-            my_kind: ErrorKind::Other(string),
-        }
+        Self::new(ErrorKind::Other(string))
     }
 }
 
 impl std::convert::From<std::sync::mpsc::RecvError> for Error {
-    fn from(_err: std::sync::mpsc::RecvError) -> Self {
-        Error {
-            my_kind: ErrorKind::ReceiveError,
-        }
+    fn from(err: std::sync::mpsc::RecvError) -> Self {
+        Self::with_source(ErrorKind::ReceiveError, err)
     }
 }
 
 impl std::convert::From<reqwest::Error> for Error {
     fn from(err: reqwest::Error) -> Self {
-        Error {
-            my_kind: ErrorKind::Other(err.to_string()),
-        }
+        Self::with_source(ErrorKind::Http, err)
     }
 }
 
 impl std::convert::From<serde_json::error::Error> for Error {
     fn from(err: serde_json::error::Error) -> Self {
-        Self {
-            my_kind: ErrorKind::NotParsable(err.to_string()),
-        }
+        Self::with_source(ErrorKind::NotParsable(String::from("json")), err)
+    }
+}
+
+impl std::convert::From<toml::de::Error> for Error {
+    fn from(err: toml::de::Error) -> Self {
+        Self::with_source(ErrorKind::NotParsable(String::from("toml")), err)
     }
 }
 
 impl std::convert::From<base64::DecodeError> for Error {
     fn from(err: base64::DecodeError) -> Self {
-        Self {
-            my_kind: ErrorKind::NotParsable(err.description().to_string()),
-        }
+        Self::with_source(ErrorKind::NotParsable(String::from("base64")), err)
     }
 }
 
+impl std::convert::From<bson::EncoderError> for Error {
+    fn from(err: bson::EncoderError) -> Self {
+        Self::with_source(ErrorKind::NotParsable(String::from("bson")), err)
+    }
+}
+
+impl std::convert::From<bson::DecoderError> for Error {
+    fn from(err: bson::DecoderError) -> Self {
+        Self::with_source(ErrorKind::NotParsable(String::from("bson")), err)
+    }
+}
+
+/// connection problems are of the Kind StorageUnavailable, all others of the Kind Database
 impl std::convert::From<mongodb::error::Error> for Error {
     fn from(err: mongodb::error::Error) -> Self {
-        Self {
-            my_kind: ErrorKind::Other(err.description().to_string()),
+        match err {
+            mongodb::error::Error::IoError(_) => {
+                Self::with_source(ErrorKind::StorageUnavailable, err)
+            }
+            _ => Self::with_source(ErrorKind::Database, err),
         }
     }
 }
 
 /// implement std::cmp::PartialEq for Error to provied the `==` operator
+/// only the kinds are compared
 impl std::cmp::PartialEq for Error {
     fn eq(&self, other: &Error) -> bool {
         self.my_kind == other.my_kind
@@ -223,6 +353,12 @@ pub enum ErrorKind {
     /// Version Not Parsable error, used if the version cannot be parsed
     VersionNotParsable(String),
 
+    /// Schema Not Supported error, raised when a stored document has a newer schema version
+    SchemaNotSupported(i32),
+
+    /// Course Not Parsable error, raised when a course string does not match the course grammar
+    CourseNotParsable(String),
+
     /// Pool To Small is returned when the Threapool is to small to be created
     PoolToSmall,
 
@@ -237,6 +373,34 @@ pub enum ErrorKind {
     /// ReceiveError reaise when mpsc cannot receive any data
     ReceiveError,
 
+    /// Http error, raised when a request fails, the reqwest error is the source
+    Http,
+
+    /// Database error, raised by the storage backend, the mongodb error is the source
+    Database,
+
+    /// Storage Unavailable error, raised when the database cannot be reached
+    StorageUnavailable,
+
+    /// Auth Failed error, raised when the upstream rejects the credentials
+    AuthFailed(String),
+
+    /// Auth Expired error, raised when the session of the upstream ended and cannot be renewed
+    AuthExpired(String),
+
+    /// Upstream Status error, raised when the upstream answers with an error status
+    UpstreamStatus(u16),
+
+    /// Rate Limited error, raised when the upstream answers with `429 Too Many Requests`
+    RateLimited,
+
+    /// Layout Changed error, raised when a page or json of the upstream misses an expected part
+    /// holds the path of the part
+    LayoutChanged(String),
+
+    /// Empty Plan error, raised when the upstream serves a plan without content
+    EmptyPlan(String),
+
     /// Other error, used for string to error conversion
     Other(String),
 
@@ -271,14 +435,26 @@ impl ErrorKind {
             ErrorKind::NotParsable(data) => format!("NotParsable({})", data),
             ErrorKind::NoVersionSupplied => String::from("NoVersionSupplied"),
             ErrorKind::VersionNotParsable(data) => format!("VersionNotParsable({})", data),
+            ErrorKind::SchemaNotSupported(version) => format!("SchemaNotSupported({})", version),
+            ErrorKind::CourseNotParsable(data) => format!("CourseNotParsable({})", data),
             ErrorKind::PoolToSmall => String::from("PoolToSmall"),
             ErrorKind::PoolSendError(t) => match t {
                 true => String::from("PoolSendError(Job)"),
                 false => String::from("PoolSendError(Terminate)"),
             },
+            ErrorKind::SendError(data) => format!("SendError({})", data),
+            ErrorKind::ReceiveError => String::from("ReceiveError"),
+            ErrorKind::Http => String::from("Http"),
+            ErrorKind::Database => String::from("Database"),
+            ErrorKind::StorageUnavailable => String::from("StorageUnavailable"),
+            ErrorKind::AuthFailed(data) => format!("AuthFailed({})", data),
+            ErrorKind::AuthExpired(data) => format!("AuthExpired({})", data),
+            ErrorKind::UpstreamStatus(status) => format!("UpstreamStatus({})", status),
+            ErrorKind::RateLimited => String::from("RateLimited"),
+            ErrorKind::LayoutChanged(data) => format!("LayoutChanged({})", data),
+            ErrorKind::EmptyPlan(data) => format!("EmptyPlan({})", data),
             ErrorKind::Other(data) => format!("Other({})", data),
             ErrorKind::Unknown(data) => format!("Unknown({})", data),
-            _ => format!("Not covered??!!!\n"),
         }
     }
 
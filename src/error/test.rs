@@ -256,6 +256,18 @@ mod error_kind_error_string {
         );
     }
 
+    #[test]
+    fn schema_not_supported() {
+        let kind = ErrorKind::SchemaNotSupported(2);
+        assert_eq!(kind.error_string(), String::from("SchemaNotSupported(2)"));
+    }
+
+    #[test]
+    fn course_not_parsable() {
+        let kind = ErrorKind::CourseNotParsable(String::from("data"));
+        assert_eq!(kind.error_string(), String::from("CourseNotParsable(data)"));
+    }
+
     #[test]
     fn pool_to_small() {
         let kind = ErrorKind::PoolToSmall;
@@ -274,6 +286,72 @@ mod error_kind_error_string {
         );
     }
 
+    #[test]
+    fn send_error() {
+        let kind = ErrorKind::SendError(String::from("test"));
+        assert_eq!(kind.error_string(), String::from("SendError(test)"));
+    }
+
+    #[test]
+    fn receive_error() {
+        let kind = ErrorKind::ReceiveError;
+        assert_eq!(kind.error_string(), String::from("ReceiveError"));
+    }
+
+    #[test]
+    fn http() {
+        let kind = ErrorKind::Http;
+        assert_eq!(kind.error_string(), String::from("Http"));
+    }
+
+    #[test]
+    fn database() {
+        let kind = ErrorKind::Database;
+        assert_eq!(kind.error_string(), String::from("Database"));
+    }
+
+    #[test]
+    fn storage_unavailable() {
+        let kind = ErrorKind::StorageUnavailable;
+        assert_eq!(kind.error_string(), String::from("StorageUnavailable"));
+    }
+
+    #[test]
+    fn auth_failed() {
+        let kind = ErrorKind::AuthFailed(String::from("test"));
+        assert_eq!(kind.error_string(), String::from("AuthFailed(test)"));
+    }
+
+    #[test]
+    fn auth_expired() {
+        let kind = ErrorKind::AuthExpired(String::from("test"));
+        assert_eq!(kind.error_string(), String::from("AuthExpired(test)"));
+    }
+
+    #[test]
+    fn upstream_status() {
+        let kind = ErrorKind::UpstreamStatus(502);
+        assert_eq!(kind.error_string(), String::from("UpstreamStatus(502)"));
+    }
+
+    #[test]
+    fn rate_limited() {
+        let kind = ErrorKind::RateLimited;
+        assert_eq!(kind.error_string(), String::from("RateLimited"));
+    }
+
+    #[test]
+    fn layout_changed() {
+        let kind = ErrorKind::LayoutChanged(String::from("test"));
+        assert_eq!(kind.error_string(), String::from("LayoutChanged(test)"));
+    }
+
+    #[test]
+    fn empty_plan() {
+        let kind = ErrorKind::EmptyPlan(String::from("test"));
+        assert_eq!(kind.error_string(), String::from("EmptyPlan(test)"));
+    }
+
     #[test]
     fn other() {
         let kind = ErrorKind::Other(String::from("test"));
@@ -287,4 +365,82 @@ mod error_kind_error_string {
     }
 }
 
-// FIXME: tests
+mod chain {
+    use super::super::{Context, Error, ErrorKind};
+    use std::error::Error as _;
+    use std::io;
+
+    fn io_error() -> io::Error {
+        io::Error::new(io::ErrorKind::NotFound, "missing")
+    }
+
+    #[test]
+    fn source_is_kept() {
+        let error = Error::from(io_error());
+        assert_eq!(error.kind(), ErrorKind::IoNotFound);
+        let source = error.source().expect("source");
+        assert_eq!(source.to_string(), "missing");
+    }
+
+    #[test]
+    fn without_source() {
+        let error = Error::new(ErrorKind::NoVersionSupplied);
+        assert!(error.source().is_none());
+        assert_eq!(error.to_string(), "NoVersionSupplied");
+    }
+
+    #[test]
+    fn context_outermost_first() {
+        let error = Err::<(), _>(io_error())
+            .context("dbidx 4")
+            .context("crawl")
+            .unwrap_err();
+        assert_eq!(error.contexts(), &["dbidx 4", "crawl"]);
+        assert_eq!(error.to_string(), "crawl: dbidx 4: IoNotFound: missing");
+    }
+
+    #[test]
+    fn with_context_is_lazy() {
+        let ok: Result<i32, Error> = Ok(1);
+        let value = ok
+            .with_context(|| -> String { panic!("context of ok") })
+            .unwrap();
+        assert_eq!(value, 1);
+    }
+
+    #[test]
+    fn context_keeps_kind() {
+        let error = Error::new_field_not_exists(String::from("date")).context("tables.Q1.hours[3]");
+        assert_eq!(error, Error::new_field_not_exists(String::from("date")));
+        assert_eq!(
+            error.to_string(),
+            "tables.Q1.hours[3]: FieldNotExists(date)"
+        );
+    }
+
+    #[test]
+    fn upstream_status() {
+        assert_eq!(
+            Error::new_upstream_status(503).kind(),
+            ErrorKind::UpstreamStatus(503)
+        );
+        assert_eq!(
+            Error::new_upstream_status(429).kind(),
+            ErrorKind::RateLimited
+        );
+    }
+
+    #[test]
+    fn toml() {
+        let error = Error::from(toml::from_str::<toml::Value>("a = ").unwrap_err());
+        assert_eq!(error.kind(), ErrorKind::NotParsable(String::from("toml")));
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn json() {
+        let error = Error::from(serde_json::from_str::<i32>("{").unwrap_err());
+        assert_eq!(error.kind(), ErrorKind::NotParsable(String::from("json")));
+        assert!(error.source().is_some());
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::common::course::{strip_exam, Grade, Group};
+pub use super::common::{Course, Room, Teacher};
+pub use super::dsb::{Duration, Entry, EntryKind, DSB};
+use super::planinfo::Table;
+
+// tests as sub module
+#[cfg(test)] // only add when running tests
+mod test;
+
+/// teacher supervising an exam in the periods of time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Supervisor {
+    pub teacher: Teacher,
+    pub time: Duration,
+}
+
+/// exam of an upper school course, collected from the substitution plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Exam {
+    pub date: NaiveDate,
+
+    /// grade of the course (`Q1`), empty if the plan has none
+    pub grade: String,
+
+    /// course of the exam, always `Course::Sec2Exam`
+    pub course: Course,
+
+    /// periods of all rows of the exam
+    pub time: Duration,
+
+    /// rooms the exam is written in
+    pub rooms: Vec<Room>,
+
+    /// teachers supervising the exam
+    pub supervisors: Vec<Supervisor>,
+}
+
+impl Exam {
+    /// create exam of entry on date, None if entry is no upper school exam
+    pub fn from_entry(date: NaiveDate, entry: &Entry) -> Option<Self> {
+        if !is_exam(entry) {
+            return None;
+        }
+        let course = match (&entry.course, &entry.old_course) {
+            (Course::Sec2 { track, name, kind }, _)
+            | (Course::Sec2Exam { track, name, kind }, _)
+            | (Course::None, Course::Sec2 { track, name, kind })
+            | (Course::None, Course::Sec2Exam { track, name, kind }) => Course::Sec2Exam {
+                track: *track,
+                name: name.clone(),
+                kind: kind.clone(),
+            },
+            _ => return None,
+        };
+
+        let (grade, _) = strip_exam(&entry.name);
+        let grade = match Group::parse(&grade).ok().and_then(|v| v.grade) {
+            Some(Grade::Sec2(grade)) => grade,
+            _ => String::new(),
+        };
+        let mut exam = Self {
+            date,
+            grade,
+            course,
+            time: entry.time.clone(),
+            rooms: Vec::new(),
+            supervisors: Vec::new(),
+        };
+        exam.add(entry);
+        Some(exam)
+    }
+
+    /// key of the course of the exam (`Q1 D GK1`)
+    pub fn key(&self) -> String {
+        match &self.course {
+            Course::Sec2Exam { name, kind, .. } => format!("{} {} {}", self.grade, name, kind),
+            course => format!("{} {}", self.grade, course),
+        }
+        .trim()
+        .to_string()
+    }
+
+    /// test if other is the same exam
+    fn same(&self, other: &Exam) -> bool {
+        self.date == other.date
+            && self.grade == other.grade
+            && self
+                .course
+                .to_string()
+                .eq_ignore_ascii_case(&other.course.to_string())
+    }
+
+    /// add room, supervisor and periods of another row of the exam
+    fn add(&mut self, entry: &Entry) {
+        if entry.time.from > 0 {
+            if self.time.from == 0 || entry.time.from < self.time.from {
+                self.time.from = entry.time.from;
+            }
+            if entry.time.to > self.time.to {
+                self.time.to = entry.time.to;
+            }
+        }
+        if !entry.room.is_none() && !self.rooms.iter().any(|v| v.same(&entry.room)) {
+            self.rooms.push(entry.room.clone());
+        }
+
+        let teacher = &entry.new_teacher;
+        if teacher.name.is_empty() {
+            return;
+        }
+        match self
+            .supervisors
+            .iter_mut()
+            .find(|v| v.teacher.name.eq_ignore_ascii_case(&teacher.name))
+        {
+            Some(supervisor) => {
+                supervisor.time.from = supervisor.time.from.min(entry.time.from);
+                supervisor.time.to = supervisor.time.to.max(entry.time.to);
+            }
+            None => self.supervisors.push(Supervisor {
+                teacher: teacher.clone(),
+                time: entry.time.clone(),
+            }),
+        }
+    }
+
+    /// test if the exam is written in course (`D GK1`) of grade, grade is ignored if empty
+    pub fn is_course(&self, course: &Course, grade: &str) -> bool {
+        if !grade.is_empty() && !self.grade.is_empty() && !grade.eq_ignore_ascii_case(&self.grade) {
+            return false;
+        }
+        match (&self.course, course) {
+            (
+                Course::Sec2Exam { name, kind, .. },
+                Course::Sec2 {
+                    name: other,
+                    kind: other_kind,
+                    ..
+                },
+            ) => name.eq_ignore_ascii_case(other) && kind == other_kind,
+            _ => false,
+        }
+    }
+
+    /// test if the exam concerns the table
+    /// students enrolled in the course and the supervising teachers
+    pub fn concerns(&self, table: &Table) -> bool {
+        let grade = grade_of(table);
+        table
+            .courses
+            .iter()
+            .any(|v| self.is_course(&v.course, &grade))
+            || self
+                .supervisors
+                .iter()
+                .any(|v| v.teacher.name.eq_ignore_ascii_case(&table.name))
+    }
+}
+
+/// test if entry is an exam, marked in the class, the course, the kind or the message
+pub fn is_exam(entry: &Entry) -> bool {
+    let marked = |v: &str| v.to_lowercase().contains("klausur");
+    matches!(entry.course, Course::Sec2Exam { .. })
+        || matches!(entry.old_course, Course::Sec2Exam { .. })
+        || marked(&entry.name)
+        || marked(&entry.message)
+        || matches!(entry.kind, EntryKind::Exam)
+}
+
+/// get the upper school grade of a student table by the tutor group (`EFk` -> `EF`)
+/// empty if unknown
+fn grade_of(table: &Table) -> String {
+    for v in table.courses.iter().filter(|v| v.is_tutor) {
+        let name = v.course.to_string();
+        if let Some(Grade::Sec2(grade)) = name
+            .get(..2)
+            .and_then(|v| Group::parse(v).ok())
+            .and_then(|v| v.grade)
+        {
+            return grade;
+        }
+    }
+    String::new()
+}
+
+/// collect the exams of the substitution plan, rows of the same exam are merged
+pub fn collect(dsb: &DSB) -> Vec<Exam> {
+    let mut ret: Vec<Exam> = Vec::new();
+    for entry in dsb.entries.iter() {
+        let exam = match Exam::from_entry(dsb.date, entry) {
+            Some(exam) => exam,
+            None => continue,
+        };
+        match ret.iter_mut().find(|v| v.same(&exam)) {
+            Some(other) => other.add(entry),
+            None => ret.push(exam),
+        }
+    }
+    ret
+}
+
+/// sort exams into a calendar by the course key (`Q1 D GK1`), each course sorted by date
+pub fn calendar(exams: Vec<Exam>) -> BTreeMap<String, Vec<Exam>> {
+    let mut ret: BTreeMap<String, Vec<Exam>> = BTreeMap::new();
+    for exam in exams {
+        ret.entry(exam.key()).or_default().push(exam);
+    }
+    for exams in ret.values_mut() {
+        exams.sort_by_key(|v| (v.date, v.time.from));
+    }
+    ret
+}
@@ -0,0 +1,118 @@
+use super::*;
+
+use crate::common::{CourseKind, Layout};
+use crate::planinfo::Enrolment;
+
+fn date() -> NaiveDate {
+    NaiveDate::from_ymd(2019, 9, 12)
+}
+
+fn entry(class: &str, course: &str, time: (i16, i16), teacher: &str, room: &str) -> Entry {
+    let mut entry = Entry::new_from_str(class);
+    entry.course = Course::parse(Layout::Dsb, class, course).unwrap();
+    entry.time = Duration {
+        from: time.0,
+        to: time.1,
+    };
+    entry.new_teacher.name = teacher.to_string();
+    entry.room = Room::from_dsb_str(room);
+    entry
+}
+
+fn dsb() -> DSB {
+    let mut dsb = DSB::new();
+    dsb.date = date();
+    dsb.entries = vec![
+        entry("Q1", "Klausur D-GK1", (3, 3), "OTT", "A114"),
+        entry("Q1", "Klausur D-GK1", (4, 4), "BERG", "A114"),
+        entry("Q1", "Klausur D-GK1", (3, 4), "KRD", "A115"),
+        entry("Q1", "M-GK1", (2, 2), "FOM", "A010"),
+        entry("5a", "D", (1, 1), "MUE", "B012"),
+    ];
+    // exam marked by the kind of the row only
+    let mut bi = entry("EF", "BI-LK1", (1, 2), "", "B127");
+    bi.kind = EntryKind::Exam;
+    dsb.entries.push(bi);
+    dsb
+}
+
+#[test]
+fn collect_merges_rows() {
+    let exams = collect(&dsb());
+    assert_eq!(exams.len(), 2);
+
+    let d = &exams[0];
+    assert_eq!(d.key(), "Q1 D GK1");
+    assert_eq!(d.course.to_string(), "Klausur D GK1");
+    assert_eq!(d.time, Duration { from: 3, to: 4 });
+    let rooms: Vec<String> = d.rooms.iter().map(|v| v.to_string()).collect();
+    assert_eq!(rooms, vec!["A114", "A115"]);
+    let supervisors: Vec<(&str, i16, i16)> = d
+        .supervisors
+        .iter()
+        .map(|v| (v.teacher.name.as_str(), v.time.from, v.time.to))
+        .collect();
+    assert_eq!(
+        supervisors,
+        vec![("OTT", 3, 3), ("BERG", 4, 4), ("KRD", 3, 4)]
+    );
+
+    let bi = &exams[1];
+    assert_eq!(bi.key(), "EF BI LK1");
+    assert_eq!(
+        bi.course,
+        Course::Sec2Exam {
+            track: 0,
+            name: String::from("BI"),
+            kind: CourseKind::LK { number: 1 },
+        }
+    );
+    assert!(bi.supervisors.is_empty());
+}
+
+#[test]
+fn no_exams() {
+    let mut entry = Entry::new_from_str("Klausur");
+    assert!(is_exam(&entry));
+    assert!(Exam::from_entry(date(), &entry).is_none());
+
+    entry = Entry::new_from_str("Q1");
+    entry.course = Course::parse(Layout::Dsb, "Q1", "M-GK1").unwrap();
+    assert!(!is_exam(&entry));
+    entry.message = String::from("Klausur verschoben");
+    assert!(Exam::from_entry(date(), &entry).is_some());
+}
+
+#[test]
+fn calendar_by_course() {
+    let mut later = dsb();
+    later.date = date().succ();
+    later.entries.truncate(1);
+    let mut exams = collect(&later);
+    exams.append(&mut collect(&dsb()));
+
+    let calendar = calendar(exams);
+    let keys: Vec<&String> = calendar.keys().collect();
+    assert_eq!(keys, vec!["EF BI LK1", "Q1 D GK1"]);
+    let dates: Vec<NaiveDate> = calendar["Q1 D GK1"].iter().map(|v| v.date).collect();
+    assert_eq!(dates, vec![date(), date().succ()]);
+}
+
+#[test]
+fn concerns_table() {
+    let exams = collect(&dsb());
+    let mut student = Table::new();
+    student.name = String::from("Mustermann, Max");
+    student.courses = Enrolment::parse_list("Q1k-FOM, D-GK1-OTT, M-GK1-FOM");
+    assert!(exams[0].concerns(&student));
+    assert!(!exams[1].concerns(&student));
+
+    // same course in another grade
+    student.courses = Enrolment::parse_list("EFk-FOM, D-GK1-OTT");
+    assert!(!exams[0].concerns(&student));
+
+    let mut teacher = Table::new();
+    teacher.name = String::from("BERG");
+    assert!(exams[0].concerns(&teacher));
+    assert!(!exams[1].concerns(&teacher));
+}
@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+
+use bson::{bson, doc, Document};
+use serde::{Deserialize, Serialize};
+
+pub use super::common::Hour;
+pub use super::dsb::Week;
+use super::planinfo::Table;
+
+// tests as sub module
+#[cfg(test)] // only add when running tests
+mod test;
+
+/// hour of a table in a slot, one document in the index collection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// kind of the table (`teachers`, `room`, `students`, `classes`)
+    pub kind: String,
+    /// name of the table
+    pub name: String,
+    pub week: Week,
+    /// weekday (`mon`, `tue`, ...)
+    pub day: String,
+    pub period: i16,
+    pub course: String,
+    pub teacher: String,
+    pub room: String,
+    pub hour: Hour,
+    /// name of the source the table was indexed by, every source replaces only its own entries
+    #[serde(default)]
+    pub source: String,
+}
+
+impl Entry {
+    /// key of the slot of the entry, the same slot keeps its key across rebuilds
+    pub fn id(&self) -> String {
+        format!(
+            "{}/{}/{}/{:?}/{}/{}",
+            self.source, self.kind, self.name, self.week, self.day, self.period
+        )
+    }
+}
+
+/// get the name of the day column of a table (`mon`, `tue`, ...)
+pub fn day_name(day: usize) -> Option<&'static str> {
+    ["mon", "tue", "wed", "thu", "fri", "sat", "sun"]
+        .get(day)
+        .cloned()
+}
+
+/// get the index entries of a table, empty hours are skipped
+pub fn entries(kind: &str, table: &Table) -> Vec<Entry> {
+    let mut ret = Vec::new();
+    for (week, hours) in [(Week::A, &table.table_a), (Week::B, &table.table_b)].iter() {
+        for (x, day) in hours.iter().enumerate() {
+            let weekday = match day_name(x) {
+                Some(weekday) => weekday,
+                None => continue,
+            };
+            for (y, hour) in day.iter().enumerate() {
+                if hour.is_empty() {
+                    continue;
+                }
+                let mut room = hour.room.to_string();
+                if room.is_empty() && kind == "room" {
+                    room = table.name.trim().to_string();
+                }
+                ret.push(Entry {
+                    kind: kind.to_string(),
+                    name: table.name.trim().to_string(),
+                    week: *week,
+                    day: weekday.to_string(),
+                    period: y as i16 + 1,
+                    course: hour.course.to_string(),
+                    teacher: hour.teacher.name.clone(),
+                    room,
+                    hour: hour.clone(),
+                    source: String::new(),
+                });
+            }
+        }
+    }
+    ret
+}
+
+/// query on the index, unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    pub kind: Option<String>,
+    pub name: Option<String>,
+    pub course: Option<String>,
+    pub teacher: Option<String>,
+    pub room: Option<String>,
+    pub week: Option<Week>,
+    pub day: Option<String>,
+    pub period: Option<i16>,
+}
+
+impl Query {
+    /// create new empty query
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// read query from url parameters, returns None on invalid values
+    pub fn from_params(params: &BTreeMap<String, String>) -> Option<Self> {
+        let mut query = Self::new();
+        for (key, value) in params.iter() {
+            match key.as_str() {
+                "kind" => query.kind = Some(value.to_string()),
+                "name" => query.name = Some(value.to_string()),
+                "course" => query.course = Some(value.to_string()),
+                "teacher" => query.teacher = Some(value.to_string()),
+                "room" => query.room = Some(value.to_string()),
+                "week" => match value.to_uppercase().chars().next().map(Week::parse) {
+                    Some(week) if week.is_ab() => query.week = Some(week),
+                    _ => return None,
+                },
+                "day" => query.day = Some(value.to_lowercase()),
+                "period" => query.period = Some(value.parse().ok()?),
+                _ => (),
+            }
+        }
+        Some(query)
+    }
+
+    /// create mongodb filter of the query
+    pub fn to_doc(&self) -> Document {
+        let mut ret = Document::new();
+        let strings = [
+            ("kind", &self.kind),
+            ("name", &self.name),
+            ("course", &self.course),
+            ("teacher", &self.teacher),
+            ("room", &self.room),
+            ("day", &self.day),
+        ];
+        for (key, value) in strings.iter() {
+            if let Some(value) = value {
+                // names are compared case insensitive
+                let regex = format!("^{}$", regex_escape(value.trim()));
+                ret.insert(*key, doc! { "$regex": regex, "$options": "i" });
+            }
+        }
+        if let Some(week) = self.week {
+            ret.insert("week", if week == Week::A { "A" } else { "B" });
+        }
+        if let Some(period) = self.period {
+            ret.insert("period", i32::from(period));
+        }
+        ret
+    }
+}
+
+/// escape regex characters of input
+fn regex_escape(input: &str) -> String {
+    let mut ret = String::new();
+    for c in input.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            ret.push('\\');
+        }
+        ret.push(c);
+    }
+    ret
+}
@@ -0,0 +1,102 @@
+//! test file for the index
+
+use super::{entries, Query, Week};
+use crate::planinfo::PlanInfo;
+use bson::{bson, doc};
+use std::collections::BTreeMap;
+
+fn student() -> PlanInfo {
+    let html = String::from_utf8_lossy(include_bytes!("../planinfo/planinfo.html"));
+    let mut planinfo = PlanInfo::new();
+    planinfo
+        .parse_str(&format!("<!DOCTYPE html>\n{}", html))
+        .unwrap();
+    planinfo
+}
+
+#[test]
+fn student_entries() {
+    let planinfo = student();
+    let entries = entries("students", &planinfo.students[0]);
+    let first = &entries[0];
+    assert_eq!(first.kind, "students");
+    assert_eq!(first.week, Week::A);
+    assert_eq!(first.day, "mon");
+    assert_eq!(first.period, 1);
+    assert_eq!(first.course, "RK GK1");
+    assert_eq!(first.teacher, "MEB");
+    assert_eq!(first.room, "A212");
+
+    // empty hours are not in the index
+    assert!(!entries
+        .iter()
+        .any(|v| v.week == Week::A && v.day == "mon" && v.period == 7));
+    // thursday 5th period in the B week
+    assert!(entries
+        .iter()
+        .any(|v| v.week == Week::B && v.day == "thu" && v.period == 5 && v.teacher == "ALBR"));
+}
+
+#[test]
+fn query_params() {
+    let mut params = BTreeMap::new();
+    params.insert("teacher".to_string(), "Mue".to_string());
+    params.insert("day".to_string(), "Tue".to_string());
+    params.insert("period".to_string(), "3".to_string());
+    params.insert("week".to_string(), "a".to_string());
+    let query = Query::from_params(&params).unwrap();
+    assert_eq!(query.week, Some(Week::A));
+
+    let filter = query.to_doc();
+    assert_eq!(
+        filter.get_document("teacher").unwrap(),
+        &doc! { "$regex": "^Mue$", "$options": "i" }
+    );
+    assert_eq!(filter.get_str("week").unwrap(), "A");
+    assert_eq!(filter.get_i32("period").unwrap(), 3);
+    assert!(filter.get("room").is_none());
+}
+
+#[test]
+fn query_invalid() {
+    let mut params = BTreeMap::new();
+    params.insert("period".to_string(), "third".to_string());
+    assert!(Query::from_params(&params).is_none());
+
+    let mut params = BTreeMap::new();
+    params.insert("week".to_string(), "C".to_string());
+    assert!(Query::from_params(&params).is_none());
+}
+
+#[test]
+fn query_escape() {
+    let mut query = Query::new();
+    query.course = Some("D GK1 (2)".to_string());
+    let filter = query.to_doc();
+    assert_eq!(
+        filter
+            .get_document("course")
+            .unwrap()
+            .get_str("$regex")
+            .unwrap(),
+        "^D GK1 \\(2\\)$"
+    );
+}
+
+#[test]
+fn entry_id() {
+    let planinfo = student();
+    let entries = entries("students", &planinfo.students[0]);
+    // every slot has its own key
+    let mut ids: Vec<String> = entries.iter().map(|v| v.id()).collect();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), entries.len());
+
+    // the key does not depend on the content of the slot
+    let mut moved = entries[0].clone();
+    moved.room = String::from("B106");
+    assert_eq!(moved.id(), entries[0].id());
+    moved.source = String::from("files");
+    assert_ne!(moved.id(), entries[0].id());
+}
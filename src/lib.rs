@@ -3,12 +3,33 @@
 /// common data types
 pub mod common;
 
+/// school calendar with A/B weeks
+pub mod calendar;
+
+/// bell schedules mapping periods to times
+pub mod bells;
+
 /// dsb parser, loader and config
 pub mod dsb;
 
 /// planinfo parser, loader and config
 pub mod planinfo;
 
+/// reverse index over the planinfo tables
+pub mod index;
+
+/// free room finder
+pub mod rooms;
+
+/// exam calendar of the upper school courses
+pub mod exams;
+
+/// teacher directory with the full names of the short names
+pub mod teachers;
+
+/// sources of substitution plans and timetables
+pub mod source;
+
 /// error structs
 pub mod error;
 
@@ -30,9 +51,24 @@ pub struct Config {
     /// config for planinfo parser
     pub planino: planinfo::Config,
 
+    /// config for a directory of dropped html files, not read if None
+    pub files: Option<source::files::Config>,
+
     /// config for storage
     pub storage: storage::Config,
 
+    /// config for the school calendar
+    pub calendar: calendar::Config,
+
+    /// config for the bell schedules
+    pub bells: bells::Config,
+
+    /// patterns and aliases for the rooms of the school
+    pub rooms: common::RoomParser,
+
+    /// config for the teacher directory
+    pub teachers: teachers::Config,
+
     /// url to impressum of host
     pub impressum: String,
 
@@ -50,7 +86,12 @@ impl Config {
             verbose: 0,
             dsb: dsb::Config::new(),
             planino: planinfo::Config::new(),
+            files: None,
             storage: storage::Config::new(),
+            calendar: calendar::Config::new(),
+            bells: bells::Config::new(),
+            rooms: common::RoomParser::new(),
+            teachers: teachers::Config::new(),
             impressum: String::from("localhost"),
             port: 8080,
             address: String::from("0.0.0.0"),
@@ -63,14 +104,33 @@ impl Config {
 
         let mongo = self.storage.connect()?;
 
-        self.dsb.run(mongo.clone())?;
+        let mut calendar = self.calendar.clone();
+        calendar.import()?;
 
-        self.planino.run(mongo.clone())?;
+        let mut teachers = self.teachers.clone();
+        teachers.import()?;
 
-        // run server
-        let server = server::Server::new(&self);
-        server.run();
+        let mut sources: Vec<Box<dyn source::Source>> =
+            vec![Box::new(self.dsb.clone()), Box::new(self.planino.clone())];
+        if !self.dsb.teacher_timetable.is_empty() {
+            sources.push(Box::new(dsb::TeacherPlan::new(self.dsb.clone())));
+        }
+        if let Some(files) = &self.files {
+            sources.push(Box::new(files.clone()));
+        }
+        let status = source::Status::default();
+        for v in sources {
+            source::run(
+                v,
+                mongo.clone(),
+                calendar.clone(),
+                status.clone(),
+                self.verbose,
+            );
+        }
 
-        Ok(())
+        // run server
+        let server = server::Server::new(self, mongo.clone(), calendar, teachers, status);
+        server.run()
     }
 }
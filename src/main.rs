@@ -51,6 +51,14 @@ fn main() {
                 .takes_value(true)
                 .value_name("PASSWORD"),
         )
+        .arg(
+            Arg::with_name("dsb.mode")
+                .long("dsb.mode")
+                .help("set way to get the dsb plans")
+                .takes_value(true)
+                .possible_values(&["web", "token"])
+                .value_name("MODE"),
+        )
         .arg(
             Arg::with_name("planinfo.baseurl")
                 .long("plainfo.baseurl")
@@ -73,6 +81,20 @@ fn main() {
                 .takes_value(true)
                 .value_name("COOKIE"),
         )
+        .arg(
+            Arg::with_name("planinfo.user")
+                .long("planinfo.user")
+                .help("set user for the planinfo login")
+                .takes_value(true)
+                .value_name("USER"),
+        )
+        .arg(
+            Arg::with_name("planinfo.password")
+                .long("planinfo.password")
+                .help("set password for the planinfo login")
+                .takes_value(true)
+                .value_name("PASSWORD"),
+        )
         .arg(
             Arg::with_name("planinfo.max_misses")
                 .long("planinfo.misses")
@@ -261,6 +283,47 @@ fn main() {
         }
     }
 
+    if let Some(mode) = &matches.value_of("dsb.mode") {
+        if let Some(mode) = llg_companion::dsb::Mode::parse(mode) {
+            conf.dsb.mode = mode;
+        }
+    } else if let Some(config) = &config {
+        if let Some(dsb) = config.get("dsb") {
+            if let Some(mode) = dsb.get("mode") {
+                if let Some(mode) = mode.as_str() {
+                    match llg_companion::dsb::Mode::parse(mode) {
+                        Some(mode) => conf.dsb.mode = mode,
+                        None => eprintln!("Error: Config: unknown dsb mode {}", mode),
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(config) = &config {
+        if let Some(dsb) = config.get("dsb") {
+            if let Some(url) = dsb.get("api_url") {
+                if let Some(url) = url.as_str() {
+                    conf.dsb.api_url = url.to_string();
+                }
+            }
+            if let Some(timetable) = dsb.get("timetable") {
+                if let Some(timetable) = timetable.as_str() {
+                    conf.dsb.timetable = timetable.to_string();
+                }
+            }
+            if let Some(timetable) = dsb.get("teacher_timetable").and_then(|v| v.as_str()) {
+                conf.dsb.teacher_timetable = timetable.to_string();
+            }
+            if let Some(kinds) = dsb.get("kinds") {
+                match kinds.clone().try_into() {
+                    Ok(kinds) => conf.dsb.kinds = llg_companion::dsb::valid_kinds(kinds),
+                    Err(err) => eprintln!("Error parsing dsb kinds: {}", err),
+                }
+            }
+        }
+    }
+
     if let Some(url) = &matches.value_of("planinfo.baseurl") {
         conf.planino.base_url = url.to_string();
     } else if let Some(config) = &config {
@@ -297,6 +360,44 @@ fn main() {
         }
     }
 
+    if let Some(user) = &matches.value_of("planinfo.user") {
+        conf.planino.user = user.to_string();
+    } else if let Some(config) = &config {
+        if let Some(planinfo) = config.get("planinfo") {
+            if let Some(user) = planinfo.get("user") {
+                if let Some(user) = user.as_str() {
+                    conf.planino.user = user.to_string();
+                }
+            }
+        }
+    }
+
+    if let Some(password) = &matches.value_of("planinfo.password") {
+        conf.planino.password = password.to_string();
+    } else if let Some(config) = &config {
+        if let Some(planinfo) = config.get("planinfo") {
+            if let Some(password) = planinfo.get("password") {
+                if let Some(password) = password.as_str() {
+                    conf.planino.password = password.to_string();
+                }
+            }
+        }
+    }
+
+    if let Some(config) = &config {
+        if let Some(planinfo) = config.get("planinfo") {
+            if let Some(url) = planinfo.get("login_url").and_then(|v| v.as_str()) {
+                conf.planino.login_url = url.to_string();
+            }
+            if let Some(field) = planinfo.get("user_field").and_then(|v| v.as_str()) {
+                conf.planino.user_field = field.to_string();
+            }
+            if let Some(field) = planinfo.get("password_field").and_then(|v| v.as_str()) {
+                conf.planino.password_field = field.to_string();
+            }
+        }
+    }
+
     if let Some(max_misses) = &matches.value_of("planinfo.max_misses") {
         conf.planino.max_misses = max_misses.parse().unwrap_or(conf.planino.max_misses);
     } else if let Some(config) = &config {
@@ -361,6 +462,137 @@ fn main() {
         }
     }
 
+    if let Some(config) = &config {
+        if let Some(rooms) = config.get("rooms") {
+            match rooms
+                .clone()
+                .try_into::<llg_companion::common::RoomParser>()
+            {
+                Ok(rooms) => conf.rooms = rooms,
+                Err(err) => eprintln!("Error parsing rooms: {}", err),
+            }
+        }
+    }
+    conf.dsb.room_parser = conf.rooms.clone();
+    conf.planino.room_parser = conf.rooms.clone();
+
+    if let Some(config) = &config {
+        if let Some(files) = config.get("files") {
+            if let Some(path) = files.get("path").and_then(|v| v.as_str()) {
+                let mut files_conf = llg_companion::source::files::Config::new();
+                files_conf.path = path.to_string();
+                if let Some(interval) = files.get("interval").and_then(|v| v.as_integer()) {
+                    files_conf.interval = std::time::Duration::from_secs(interval as u64);
+                }
+                files_conf.room_parser = conf.rooms.clone();
+                files_conf.kinds = conf.dsb.kinds.clone();
+                files_conf.verbose = conf.verbose;
+                conf.files = Some(files_conf);
+            }
+        }
+    }
+
+    if let Some(config) = &config {
+        if let Some(calendar) = config.get("calendar") {
+            if let Some(days) = calendar.get("days") {
+                if let Some(days) = days.as_integer() {
+                    conf.calendar.days = days;
+                }
+            }
+            if let Some(holidays) = calendar.get("holidays").and_then(|v| v.as_array()) {
+                for v in holidays.iter().filter_map(|v| v.as_str()) {
+                    match chrono::NaiveDate::parse_from_str(v, "%Y-%m-%d") {
+                        Ok(date) => {
+                            conf.calendar.holidays.insert(date);
+                        }
+                        Err(err) => eprintln!("Error parsing holiday {{{}}}: {}", v, err),
+                    }
+                }
+            }
+            if let Some(ical) = calendar.get("ical").and_then(|v| v.as_array()) {
+                for v in ical.iter().filter_map(|v| v.as_str()) {
+                    conf.calendar.ical.push(v.to_string());
+                }
+            }
+            if let Some(overrides) = calendar.get("overrides") {
+                if let Some(overrides) = overrides.as_array() {
+                    for v in overrides.iter() {
+                        let date = v.get("date").and_then(|v| v.as_str()).unwrap_or("");
+                        let week = v.get("week").and_then(|v| v.as_str()).unwrap_or("");
+                        match (
+                            chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d"),
+                            week.chars().next(),
+                        ) {
+                            (Ok(date), Some(week)) => conf
+                                .calendar
+                                .overrides
+                                .push((date, llg_companion::dsb::Week::parse(week))),
+                            _ => eprintln!(
+                                "Error parsing calendar override: {{{}}} {{{}}}",
+                                date, week
+                            ),
+                        }
+                    }
+                }
+            }
+        }
+    }
+    conf.calendar.verbose = conf.verbose;
+
+    if let Some(config) = &config {
+        if let Some(teachers) = config.get("teachers") {
+            if let Some(files) = teachers.get("files").and_then(|v| v.as_array()) {
+                for v in files.iter().filter_map(|v| v.as_str()) {
+                    conf.teachers.files.push(v.to_string());
+                }
+            }
+            if let Some(public) = teachers.get("public").and_then(|v| v.as_bool()) {
+                conf.teachers.public = public;
+            }
+        }
+    }
+    conf.teachers.verbose = conf.verbose;
+
+    if let Some(config) = &config {
+        if let Some(bells) = config.get("bells") {
+            if let Some(schedules) = bells.get("schedules").and_then(|v| v.as_table()) {
+                for (name, periods) in schedules.iter() {
+                    let periods: Vec<&str> = periods
+                        .as_array()
+                        .map(|v| v.iter().filter_map(|v| v.as_str()).collect())
+                        .unwrap_or_default();
+                    match llg_companion::bells::Schedule::from_strs(&periods) {
+                        Some(schedule) => {
+                            conf.bells.schedules.insert(name.to_string(), schedule);
+                        }
+                        None => eprintln!("Error parsing bell schedule: {{{}}}", name),
+                    }
+                }
+            }
+            if let Some(default) = bells.get("default").and_then(|v| v.as_str()) {
+                conf.bells.default = default.to_string();
+            }
+            if let Some(weekdays) = bells.get("weekdays").and_then(|v| v.as_table()) {
+                for (weekday, name) in weekdays.iter() {
+                    if let Some(name) = name.as_str() {
+                        conf.bells
+                            .weekdays
+                            .insert(weekday.to_lowercase(), name.to_string());
+                    }
+                }
+            }
+            if let Some(buildings) = bells.get("buildings").and_then(|v| v.as_table()) {
+                for (building, name) in buildings.iter() {
+                    if let Some(name) = name.as_str() {
+                        conf.bells
+                            .buildings
+                            .insert(building.to_uppercase(), name.to_string());
+                    }
+                }
+            }
+        }
+    }
+
     if let Some(impressum) = &matches.value_of("impressum") {
         conf.impressum = impressum.to_string();
     } else if let Some(config) = &config {
@@ -0,0 +1,98 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// valid dbidx of planinfo and the table found there
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Known {
+    pub dbidx: i64,
+    /// kind of the table (`teachers`, `room`, `students`, `classes`)
+    pub kind: String,
+    pub name: String,
+    /// last time the table was found
+    pub seen: NaiveDateTime,
+}
+
+/// progress of the planinfo crawler, stored after every page
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Crawl {
+    /// known valid dbidx, sorted
+    pub known: Vec<Known>,
+
+    /// last visited dbidx of the running crawl
+    pub position: i64,
+
+    /// start of the running crawl, None if no crawl is running
+    pub started: Option<NaiveDateTime>,
+}
+
+impl Crawl {
+    /// create new empty crawl
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// start a crawl, a running crawl is resumed
+    /// returns false if a crawl is resumed
+    pub fn begin(&mut self, start: usize, now: NaiveDateTime) -> bool {
+        if self.started.is_some() {
+            return false;
+        }
+        self.started = Some(now);
+        self.position = start as i64;
+        true
+    }
+
+    /// finish the running crawl
+    pub fn finish(&mut self) {
+        self.started = None;
+        self.position = 0;
+    }
+
+    /// get the last known dbidx
+    pub fn last_known(&self) -> Option<i64> {
+        self.known.last().map(|v| v.dbidx)
+    }
+
+    /// get the next dbidx to visit: the known indices and `window` indices after the last hit
+    /// `end` of 0 means no end
+    pub fn next(&self, start: usize, end: usize, window: usize) -> Option<i64> {
+        let position = self.position;
+        let anchor = self.last_known().unwrap_or(start as i64).max(start as i64);
+
+        let next = match self.known.iter().find(|v| v.dbidx > position) {
+            Some(known) => known.dbidx,
+            None if position < anchor + window as i64 => position + 1,
+            None => return None,
+        };
+        if end != 0 && next > end as i64 {
+            return None;
+        }
+        Some(next)
+    }
+
+    /// remember table found at dbidx
+    pub fn hit(&mut self, dbidx: i64, kind: &str, name: &str, now: NaiveDateTime) {
+        let known = Known {
+            dbidx,
+            kind: kind.to_string(),
+            name: name.trim().to_string(),
+            seen: now,
+        };
+        match self.known.binary_search_by_key(&dbidx, |v| v.dbidx) {
+            Ok(i) => self.known[i] = known,
+            Err(i) => self.known.insert(i, known),
+        }
+    }
+
+    /// forget dbidx, there is no table anymore
+    pub fn miss(&mut self, dbidx: i64) {
+        self.known.retain(|v| v.dbidx != dbidx);
+    }
+
+    /// test if a table of kind with name is known
+    pub fn is_known(&self, kind: &str, name: &str) -> bool {
+        self.known
+            .iter()
+            .any(|v| v.kind == kind && v.name == name.trim())
+    }
+}
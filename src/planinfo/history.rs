@@ -0,0 +1,99 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use super::{Hour, Table, Week};
+use crate::index::day_name;
+
+/// old and new value of a changed field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diff {
+    pub old: String,
+    pub new: String,
+}
+
+impl Diff {
+    /// get diff if old and new differ
+    fn new(old: String, new: String) -> Option<Self> {
+        if old == new {
+            None
+        } else {
+            Some(Self { old, new })
+        }
+    }
+}
+
+/// change of one hour of a table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlotChange {
+    pub week: Week,
+    /// weekday (`mon`, `tue`, ...)
+    pub day: String,
+    pub period: i16,
+    pub course: Option<Diff>,
+    pub room: Option<Diff>,
+    pub teacher: Option<Diff>,
+}
+
+/// changes of a table between two versions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Change {
+    /// kind of the table (`teachers`, `room`, `students`, `classes`)
+    pub kind: String,
+    pub name: String,
+    /// version of the table with the changes
+    pub version: i64,
+    /// time the changes were found
+    pub date: NaiveDateTime,
+    pub slots: Vec<SlotChange>,
+}
+
+impl Change {
+    /// create change between old and new table
+    pub fn new(kind: &str, old: &Table, new: &Table, date: NaiveDateTime) -> Self {
+        Self {
+            kind: kind.to_string(),
+            name: new.name.trim().to_string(),
+            version: new.version,
+            date,
+            slots: diff(old, new),
+        }
+    }
+}
+
+/// get the changed hours between old and new table
+pub fn diff(old: &Table, new: &Table) -> Vec<SlotChange> {
+    let empty = Hour::new();
+    let mut ret = Vec::new();
+    let weeks = [
+        (Week::A, &old.table_a, &new.table_a),
+        (Week::B, &old.table_b, &new.table_b),
+    ];
+    for (week, old, new) in weeks.iter() {
+        let days = old.len().max(new.len());
+        for x in 0..days {
+            let periods = old
+                .get(x)
+                .map_or(0, |v| v.len())
+                .max(new.get(x).map_or(0, |v| v.len()));
+            for y in 0..periods {
+                let old = old.get(x).and_then(|v| v.get(y)).unwrap_or(&empty);
+                let new = new.get(x).and_then(|v| v.get(y)).unwrap_or(&empty);
+                if old == new {
+                    continue;
+                }
+                let change = SlotChange {
+                    week: *week,
+                    day: day_name(x).unwrap_or("").to_string(),
+                    period: y as i16 + 1,
+                    course: Diff::new(old.course.to_string(), new.course.to_string()),
+                    room: Diff::new(old.room.to_string(), new.room.to_string()),
+                    teacher: Diff::new(old.teacher.name.clone(), new.teacher.name.clone()),
+                };
+                if change.course.is_some() || change.room.is_some() || change.teacher.is_some() {
+                    ret.push(change);
+                }
+            }
+        }
+    }
+    ret
+}
@@ -1,16 +1,31 @@
 use html5ever::parse_document;
 use html5ever::rcdom::{Handle, Node, NodeData, RcDom};
 use html5ever::tendril::TendrilSink;
-use reqwest::header;
 
+use super::dsb::node_text;
 pub use super::error::Error;
+use super::error::ErrorKind;
 #[doc(inline)]
 pub use super::error::Result;
-use super::storage::MongoDB;
+use super::source::{Sink, Source};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-pub use super::common::{Hour, Room, Teacher};
+pub use super::common::{Course, Hour, Layout, Room, RoomParser, Teacher};
+pub use super::dsb::Week;
+
+/// progress of the crawler
+pub mod crawl;
+
+/// changes between versions of tables
+pub mod history;
+
+/// login and session handling
+pub mod session;
+
+// tests as sub module
+#[cfg(test)] // only add when running tests
+mod test;
 
 /// config struct for planinfo
 #[derive(Clone)]
@@ -22,13 +37,29 @@ pub struct Config {
     /// school id to identify the school to planinfo
     pub school_id: String,
 
-    /// cookies for auth at planinfo
+    /// cookies for auth at planinfo, not needed if user is set
     pub cookies: String,
 
+    /// user to login at planinfo
+    pub user: String,
+
+    /// password to login at planinfo
+    pub password: String,
+
+    /// url of the login form
+    /// is a hidden setting, and default to `https://selbstlernportal.de/html/planinfo/planinfo_login.inc.php`
+    pub login_url: String,
+
+    /// name of the user field of the login form, hidden setting
+    pub user_field: String,
+
+    /// name of the password field of the login form, hidden setting
+    pub password_field: String,
+
     /// delay between hits
     pub delay_hits: std::time::Duration,
 
-    /// max times of miss
+    /// max times of miss, also the number of indices searched after the last known table
     pub max_misses: usize,
 
     /// where to start in the database
@@ -38,6 +69,9 @@ pub struct Config {
     /// where to end
     pub end: usize,
 
+    /// parser for the rooms of the school
+    pub room_parser: RoomParser,
+
     /// verbose level
     pub verbose: u8,
 }
@@ -49,166 +83,129 @@ impl Config {
             base_url: String::from("https://selbstlernportal.de/html/planinfo/planinfo_start.php"),
             school_id: String::new(),
             cookies: String::new(),
+            user: String::new(),
+            password: String::new(),
+            login_url: String::from(
+                "https://selbstlernportal.de/html/planinfo/planinfo_login.inc.php",
+            ),
+            user_field: String::from("user"),
+            password_field: String::from("passwort"),
             delay_hits: std::time::Duration::from_secs(20),
             max_misses: 5,
             start: 0,
             end: 0,
+            room_parser: RoomParser::new(),
             verbose: 0,
         }
     }
 
-    /// start parsing
-    pub fn run(&self, db: MongoDB) -> Result<()> {
-        let conf = self.clone();
-        std::thread::spawn(move || {
-            conf.run_int(db);
-        });
-        Ok(())
-    }
-
-    /// internal running function
-    fn run_int(mut self, db: MongoDB) {
-        loop {
-            let planinfo = self.run_get(db.clone()).unwrap();
-            std::thread::sleep(std::time::Duration::from_secs(86400)); // sleep for one day
+    /// redownload pages, resumes a crawl stopped before
+    fn crawl(&self, sink: &mut Sink) -> Result<crawl::Crawl> {
+        let mut planinfo = PlanInfo::new();
+        planinfo.room_parser = self.room_parser.clone();
+        let mut crawl: crawl::Crawl = sink.state_read(self.name())?.unwrap_or_default();
+        let now = chrono::Local::now().naive_local();
+        if !crawl.begin(self.start, now) && self.verbose >= 1 {
+            println!("Debug1: PlanInfo: resume crawl at dbidx {}", crawl.position);
         }
-    }
 
-    /// redownload page
-    fn run_get(&self, db: MongoDB) -> Result<PlanInfo> {
-        let mut planinfo = PlanInfo::new();
-        let mut hits = self.max_misses;
-        let mut dbidx: usize = self.start;
-
-        // build client for http
-        let mut headers = header::HeaderMap::new();
-        headers.insert(
-            header::COOKIE,
-            header::HeaderValue::from_str(&self.cookies).unwrap(),
-        );
-
-        // get a client builder
-        let client: reqwest::Client = reqwest::Client::builder()
-            .default_headers(headers)
-            .cookie_store(true)
-            .build()?;
-
-        while hits > 0 {
-            dbidx += 1;
+        let mut session = session::Session::new(self)?;
+
+        while let Some(dbidx) = crawl.next(self.start, self.end, self.max_misses) {
             if self.verbose >= 3 {
                 println!("Debug3: PlanInfo: hit dbidx {}", dbidx);
             }
-            let mut body: reqwest::Response = client
-                .get(&format!(
-                    "{}?ug={}&dbidx={}",
-                    self.base_url, self.school_id, dbidx
-                ))
-                .send()?;
-
-            if !body.status().is_success() {
-                eprintln!("Error: PlanInfo: GET: {}", body.status());
-                hits -= 1;
-            } else {
-                let body: String = body.text()?;
-                let ret = planinfo.parse_str(&body);
-                if let Err(err) = ret {
-                    eprintln!("Error: Planinfo: pars: {}", err);
-                    hits -= 1;
-                } else if let Ok((table, kind)) = ret {
-                    db.planinfo_write_table(&table, &kind);
+            let body = session.get(dbidx)?;
+            let now = chrono::Local::now().naive_local();
+            if let Some((table, kind)) = visit(&mut planinfo, &mut crawl, dbidx, body, now)? {
+                if let Err(err) = sink.timetable(&table, &kind) {
+                    eprintln!("Error: PlanInfo: write {}: {}", table.name, err);
                 }
             }
-            if dbidx == self.end {
-                hits = 0;
-            }
+            crawl.position = dbidx;
+            sink.state_write(self.name(), &crawl)?;
+
             // wait befor doing next hit
             std::thread::sleep(self.delay_hits);
         }
 
-        Ok(planinfo)
+        crawl.finish();
+        sink.state_write(self.name(), &crawl)?;
+        Ok(crawl)
+    }
+}
+
+/// parse the page of dbidx and remember the table found there in crawl
+/// a page without table (None or an empty plan) is forgotten,
+/// on other errors the crawl stops and the known tables are kept
+fn visit(
+    planinfo: &mut PlanInfo,
+    crawl: &mut crawl::Crawl,
+    dbidx: i64,
+    body: Option<String>,
+    now: chrono::NaiveDateTime,
+) -> Result<Option<(Table, String)>> {
+    let body = match body {
+        Some(body) => body,
+        None => {
+            crawl.miss(dbidx);
+            return Ok(None);
+        }
+    };
+    match planinfo.parse_str(&body) {
+        Ok((table, kind)) => {
+            crawl.hit(dbidx, &kind, &table.name, now);
+            Ok(Some((table, kind)))
+        }
+        Err(err) => match err.kind() {
+            ErrorKind::EmptyPlan(_) => {
+                crawl.miss(dbidx);
+                Ok(None)
+            }
+            _ => Err(err.context(format!("dbidx {}", dbidx))),
+        },
     }
 }
 
-fn createTable() -> [[Hour; 12]; 5] {
-    [
-        [
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-        ],
-        [
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-        ],
-        [
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-        ],
-        [
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-        ],
-        [
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-            Hour::new(),
-        ],
-    ]
+impl Source for Config {
+    fn name(&self) -> &str {
+        "PlanInfo"
+    }
+
+    fn interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(86400) // crawl once a day
+    }
+
+    /// crawl and rebuild the index from the stored tables found by the crawl
+    fn poll(&mut self, sink: &mut Sink) -> Result<()> {
+        let crawl = self.crawl(sink)?;
+        let entries = sink.rebuild_index(self.name(), |kind, name| crawl.is_known(kind, name))?;
+        if self.verbose >= 2 {
+            println!("Debug2: PlanInfo: rebuild index with {} entries", entries);
+        }
+        Ok(())
+    }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Table {
     pub name: String,
-    pub table_a: [[Hour; 12]; 5],
-    pub table_b: [[Hour; 12]; 5],
+    /// version of the table, counts the changes found by the crawls
+    #[serde(default)]
+    pub version: i64,
+    /// hours of the A week, indexed by day and period
+    pub table_a: Vec<Vec<Hour>>,
+    /// hours of the B week, indexed by day and period
+    pub table_b: Vec<Vec<Hour>>,
+    /// day labels of the header (`Montag`, ...)
+    #[serde(default)]
+    pub days: Vec<String>,
+    /// period labels of the header (`1`, ...)
+    #[serde(default)]
+    pub periods: Vec<String>,
+    /// courses of a student
+    #[serde(default)]
+    pub courses: Vec<Enrolment>,
     //pub date: chrono::DateTime<chrono::Utc>,
 }
 
@@ -216,14 +213,160 @@ impl Table {
     pub fn new() -> Self {
         Default::default()
     }
+
+    /// create table with empty hours for days and periods
+    pub fn with_size(days: usize, periods: usize) -> Self {
+        let mut table = Self::new();
+        table.resize(days, periods);
+        table
+    }
+
+    /// grow both weeks to at least days and periods, never shrinks
+    pub fn resize(&mut self, days: usize, periods: usize) {
+        for table in [&mut self.table_a, &mut self.table_b].iter_mut() {
+            if table.len() < days {
+                table.resize(days, Vec::new());
+            }
+            for day in table.iter_mut() {
+                if day.len() < periods {
+                    day.resize(periods, Hour::new());
+                }
+            }
+        }
+    }
+
+    /// get the hour of day and period, the table grows if needed
+    pub fn hour_mut(&mut self, a: bool, day: usize, period: usize) -> &mut Hour {
+        self.resize(day + 1, period + 1);
+        if a {
+            &mut self.table_a[day][period]
+        } else {
+            &mut self.table_b[day][period]
+        }
+    }
+
+    /// set the labels of the header and grow to their size
+    pub fn set_header(&mut self, days: &[String], periods: &[String]) {
+        self.days = days.to_vec();
+        self.periods = periods.to_vec();
+        self.resize(days.len(), periods.len());
+    }
+
+    /// test if the table has the same content as other, the version is ignored
+    pub fn same(&self, other: &Table) -> bool {
+        let mut table = self.clone();
+        table.version = other.version;
+        table == *other
+    }
+
+    /// get the hours of weekday in week
+    pub fn day(&self, weekday: chrono::Weekday, week: &Week) -> Option<&[Hour]> {
+        let table = match week {
+            Week::A => &self.table_a,
+            Week::B => &self.table_b,
+            Week::NoWeek(_) => return None,
+        };
+        table
+            .get(weekday.num_days_from_monday() as usize)
+            .map(|v| v.as_slice())
+    }
+
+    /// add the Sec 2 courses of the timetable missing in the course list
+    pub fn add_table_courses(&mut self) {
+        for hour in self.table_a.iter().chain(self.table_b.iter()).flatten() {
+            if let Course::Sec2 { .. } = hour.course {
+                let enrolment = Enrolment {
+                    course: hour.course.clone(),
+                    teacher: hour.teacher.clone(),
+                    is_tutor: hour.is_tutor,
+                };
+                if !self.courses.contains(&enrolment) {
+                    self.courses.push(enrolment);
+                }
+            }
+        }
+    }
+
+    /// test if the table attends course (`RK GK1`), teacher is optional
+    pub fn attends(&self, course: &str, teacher: Option<&str>) -> bool {
+        self.courses.iter().any(|v| {
+            v.course.to_string().eq_ignore_ascii_case(course.trim())
+                && match teacher {
+                    Some(teacher) => v.teacher.name.eq_ignore_ascii_case(teacher.trim()),
+                    None => true,
+                }
+        })
+    }
+}
+
+/// course a student is enrolled in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Enrolment {
+    pub course: Course,
+    pub teacher: Teacher,
+    /// the teacher is the tutor of the student
+    pub is_tutor: bool,
+}
+
+impl Enrolment {
+    /// parse course list of the student header (`EFk-FOM, D-GK11-HERM`)
+    /// entries without course kind are the tutor groups
+    pub fn parse_list(input: &str) -> Vec<Self> {
+        let mut ret = Vec::new();
+        for entry in input.split(&[',', ';', ' '][..]) {
+            let entry = entry.trim();
+            if entry.is_empty() {
+                continue;
+            }
+            let parts: Vec<&str> = entry.split('-').collect();
+            let mut teacher = Teacher::new();
+            let has_kind = parts.len() > 1 && {
+                let kind = parts[1].to_uppercase();
+                kind.starts_with("GK") || kind.starts_with("LK")
+            };
+
+            let enrolment = if has_kind {
+                if let Some(name) = parts.get(2) {
+                    teacher.name = name.to_string();
+                }
+                Enrolment {
+                    course: Course::parse(Layout::Students, "", &parts[..2].join("-"))
+                        .unwrap_or_else(|err| {
+                            eprintln!("Error: PlanInfo: Enrolment: {}", err);
+                            Course::None
+                        }),
+                    teacher,
+                    is_tutor: false,
+                }
+            } else if parts.len() == 2 {
+                teacher.name = parts[1].to_string();
+                Enrolment {
+                    course: Course::Sec1 {
+                        name: parts[0].to_string(),
+                    },
+                    teacher,
+                    is_tutor: true,
+                }
+            } else {
+                eprintln!("Error: PlanInfo: could not parse course {{{}}}", entry);
+                continue;
+            };
+            ret.push(enrolment);
+        }
+        ret
+    }
 }
 
 impl Default for Table {
     fn default() -> Self {
         Self {
             name: String::new(),
-            table_a: createTable(),
-            table_b: createTable(),
+            version: 0,
+            table_a: Vec::new(),
+            table_b: Vec::new(),
+            days: Vec::new(),
+            periods: Vec::new(),
+            courses: Vec::new(),
             //date: chrono::Utc::now(),
         }
     }
@@ -239,6 +382,12 @@ pub struct PlanInfo {
 
     /// tables for students
     pub students: Vec<Table>,
+
+    /// tables for classes
+    pub classes: Vec<Table>,
+
+    /// parser for the rooms of the school
+    pub room_parser: RoomParser,
 }
 
 impl PlanInfo {
@@ -248,6 +397,8 @@ impl PlanInfo {
             teachers: Vec::new(),
             rooms: Vec::new(),
             students: Vec::new(),
+            classes: Vec::new(),
+            room_parser: RoomParser::new(),
         }
     }
 
@@ -264,14 +415,19 @@ impl PlanInfo {
     /// parse RcDom into PlanInfo
     pub fn parse_dom(&mut self, handle: &Handle) -> Result<(Table, String)> {
         let node: &Node = handle;
-        let node: &Node = &node.children.borrow()[1];
-        if node.children.borrow().len() < 2 {
-            return Err(Error::new_field_not_exists(
+        let node = match node.children.borrow().get(1) {
+            Some(node) => node.clone(),
+            None => return Err(Error::new_layout_changed("planinfo doctype".to_string())),
+        };
+        let node: &Node = &node;
+        // head, the text between head and body and body, the body is read at index 2
+        if node.children.borrow().len() < 3 {
+            return Err(Error::new_layout_changed(
                 "planinfo html head|body".to_string(),
             ));
         }
         if !self.check_title(&node.children.borrow()[0]) {
-            return Err(Error::new_field_not_exists("planinfo auth".to_string()));
+            return Err(Error::new_auth_expired("planinfo: login page".to_string()));
         }
 
         let node: &Node = &node.children.borrow()[2];
@@ -297,14 +453,16 @@ impl PlanInfo {
                 }
             }
         }
-        Err(Error::new_field_not_exists(
-            "not found for parse_dom (planinfo)".to_string(),
-        ))
+        Err(Error::new_layout_changed("planinfo div.plan".to_string()))
     }
 
     /// parse PlanInfo plan div content
     fn parse_dom_div(&mut self, node: &Node) -> Result<(Table, String)> {
         let mut kind = 0;
+        let mut days: Vec<String> = Vec::new();
+        let mut periods: Vec<String> = Vec::new();
+        // number of days and periods of the parsed cells
+        let mut size = (0, 0);
         let node: &Node = node;
         for v in node.children.borrow().iter() {
             let v: &Node = v;
@@ -336,6 +494,12 @@ impl PlanInfo {
                                                     let name: &html5ever::QualName = name;
                                                     let attrs: &Vec<html5ever::Attribute> =
                                                         &attrs.borrow();
+                                                    if A && attrs.iter().any(|v| {
+                                                        v.name.local.to_string() == "class"
+                                                            && v.value.to_string() == "wotag"
+                                                    }) {
+                                                        days.push(node_text(v).trim().to_string());
+                                                    }
                                                     for attr in attrs.iter() {
                                                         let attr: &html5ever::Attribute = attr;
                                                         if attr.name.local.to_string() == "class"
@@ -418,7 +582,12 @@ impl PlanInfo {
                                                                     } else if contents.ends_with(
                                                                         "-Woche-Stundenplan von",
                                                                     ) {
-                                                                        return Err(Error::new_field_not_exists(("PlanInfo empty".to_string())));
+                                                                        return Err(
+                                                                            Error::new_empty_plan(
+                                                                                "planinfo"
+                                                                                    .to_string(),
+                                                                            ),
+                                                                        );
                                                                     } else if contents
                                                                         .starts_with("(")
                                                                         && contents.ends_with(")")
@@ -434,8 +603,7 @@ impl PlanInfo {
                                                                     } else if contents
                                                                         .contains("Klasse")
                                                                     {
-                                                                        // FIXME: implement class
-                                                                        /* let names: Vec<&str> =
+                                                                        let names: Vec<&str> =
                                                                             contents
                                                                                 .split(" ")
                                                                                 .collect();
@@ -445,17 +613,19 @@ impl PlanInfo {
                                                                             entryName =
                                                                                 names.to_string();
                                                                             if first_run {
-                                                                                let mut table = ClassTable::new();
+                                                                                let mut table =
+                                                                                    Table::new();
                                                                                 table.name =
-                                                                                    entryName;
+                                                                                    entryName
+                                                                                        .clone();
                                                                                 self.classes
                                                                                     .push(table);
                                                                             }
-                                                                        } */
+                                                                        }
                                                                         kind = 3;
                                                                     } else {
                                                                         eprintln!("Error: PlanInfo: parse_dom_div: unknown kind: {{{}}}", contents);
-                                                                        return Err(Error::new_field_not_exists("PlanInfo header kind".to_string()));
+                                                                        return Err(Error::new_layout_changed("planinfo header kind".to_string()));
                                                                     }
                                                                 } else if let NodeData::Element {
                                                                     ref name,
@@ -489,7 +659,9 @@ impl PlanInfo {
                                                                                             if let Some(course) = name.last() {
                                                                                                 let course: &str = course.trim();
                                                                                                 let course: &str = course.trim_matches(')');
-                                                                                                eprintln!("Error: PlanInfo: not implemented: parse course from header: {{{}}}", course);
+                                                                                                if let Some(student) = self.students.last_mut() {
+                                                                                                    student.courses = Enrolment::parse_list(course);
+                                                                                                }
                                                                                             }
                                                                                         }
                                                                                     }
@@ -520,7 +692,13 @@ impl PlanInfo {
                                                 let v: &Node = v;
                                                 if let NodeData::Element { ref name, .. } = v.data {
                                                     let name: &html5ever::QualName = name;
-                                                    if name.local.to_string() == "td" {
+                                                    if name.local.to_string() == "th" {
+                                                        if A {
+                                                            periods.push(
+                                                                node_text(v).trim().to_string(),
+                                                            );
+                                                        }
+                                                    } else if name.local.to_string() == "td" {
                                                         for v in v.children.borrow().iter() {
                                                             if let NodeData::Text { ref contents } =
                                                                 v.data
@@ -535,17 +713,13 @@ impl PlanInfo {
                                                                     {
                                                                         let table: &mut Table =
                                                                             table;
-                                                                        if A {
-                                                                            table.table_a[x][y]
-                                                                                .parse_planinfo_teacher(
-                                                                                    contents, &entryName
-                                                                                );
-                                                                        } else {
-                                                                            table.table_b[x][y]
-                                                                                .parse_planinfo_teacher(
-                                                                                    contents, &entryName
-                                                                                );
-                                                                        }
+                                                                        table
+                                                                            .hour_mut(A, x, y)
+                                                                            .parse_planinfo_teacher(
+                                                                                contents,
+                                                                                &entryName,
+                                                                                &self.room_parser,
+                                                                            );
                                                                     }
                                                                 } else if kind == 1 {
                                                                     // Room
@@ -554,11 +728,13 @@ impl PlanInfo {
                                                                     {
                                                                         let table: &mut Table =
                                                                             table;
-                                                                        if A {
-                                                                            table.table_a[x][y].parse_planinfo_room(contents, &entryName);
-                                                                        } else {
-                                                                            table.table_b[x][y].parse_planinfo_room(contents, &entryName);
-                                                                        }
+                                                                        table
+                                                                            .hour_mut(A, x, y)
+                                                                            .parse_planinfo_room(
+                                                                                contents,
+                                                                                &entryName,
+                                                                                &self.room_parser,
+                                                                            );
                                                                     }
                                                                 } else if kind == 2 {
                                                                     // student
@@ -569,26 +745,34 @@ impl PlanInfo {
                                                                             table;
                                                                         table.name =
                                                                             entryName.clone();
-                                                                        if A {
-                                                                            table.table_a[x][y].parse_planinfo_student(contents, &courseString);
-                                                                        } else {
-                                                                            table.table_b[x][y].parse_planinfo_student(contents, &courseString);
-                                                                        }
+                                                                        table
+                                                                            .hour_mut(A, x, y)
+                                                                            .parse_planinfo_student(
+                                                                                contents,
+                                                                                &courseString,
+                                                                                &self.room_parser,
+                                                                            );
                                                                     }
                                                                 } else if kind == 3 {
                                                                     // Class
-                                                                    /*if let Some(table) = self.classes.last_mut() {
-                                                                        let table: &mut ClassTable = table;
-                                                                        if A {
-                                                                            table.table_a[x][y].parse_planinfo(contents)
-                                                                        }
-                                                                    }*/
-                                                                    eprintln!("Error: PlanInfo: parser class parser not implemented");
+                                                                    if let Some(table) =
+                                                                        self.classes.last_mut()
+                                                                    {
+                                                                        let table: &mut Table =
+                                                                            table;
+                                                                        table
+                                                                            .hour_mut(A, x, y)
+                                                                            .parse_planinfo_class(
+                                                                                contents,
+                                                                                &entryName,
+                                                                                &self.room_parser,
+                                                                            );
+                                                                    }
                                                                 } else {
                                                                     return Err(
-                                                                        Error::new_field_not_exists(
+                                                                        Error::new_layout_changed(
                                                                             format!(
-                                                                                "kind: {}",
+                                                                                "planinfo kind: {}",
                                                                                 kind
                                                                             ),
                                                                         ),
@@ -597,10 +781,12 @@ impl PlanInfo {
                                                             }
                                                         }
                                                         x += 1;
+                                                        size.0 = size.0.max(x);
                                                     }
                                                 }
                                             }
                                             y += 1;
+                                            size.1 = size.1.max(y);
                                         }
                                     }
                                 }
@@ -610,28 +796,23 @@ impl PlanInfo {
                 }
             }
         }
-        let mut table: Table = Table::default();
-        let mut kindString = String::from("none");
-        if kind == 0 {
-            //teachers
-            if let Some(teacher) = self.teachers.last() {
-                table = teacher.clone();
-                kindString = "teachers".to_string();
-            }
-        } else if kind == 1 {
-            // room
-            if let Some(room) = self.rooms.last() {
-                table = room.clone();
-                kindString = "room".to_string();
-            }
-        } else if kind == 2 {
-            // student
-            if let Some(student) = self.students.last() {
-                table = student.clone();
-                kindString = "students".to_string();
-            }
+        let (tables, collection) = match kind {
+            0 => (&mut self.teachers, "teachers"),
+            1 => (&mut self.rooms, "room"),
+            2 => (&mut self.students, "students"),
+            3 => (&mut self.classes, "classes"),
+            _ => return Ok((Table::default(), String::from("none"))),
+        };
+        let table = match tables.last_mut() {
+            Some(table) => table,
+            None => return Ok((Table::default(), String::from("none"))),
+        };
+        table.set_header(&days, &periods);
+        table.resize(size.0, size.1);
+        if kind == 2 {
+            table.add_table_courses();
         }
-        Ok((table.clone(), kindString))
+        Ok((table.clone(), collection.to_string()))
     }
 
     /// test if auth was successfully
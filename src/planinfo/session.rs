@@ -0,0 +1,126 @@
+use reqwest::header;
+
+use super::Config;
+pub use super::Error;
+use super::Result;
+use crate::error::{Context, ErrorKind};
+
+/// logged in session at planinfo
+pub struct Session {
+    conf: Config,
+    client: reqwest::Client,
+}
+
+impl Session {
+    /// create session, logs in if credentials are configured
+    pub fn new(conf: &Config) -> Result<Self> {
+        let mut session = Self {
+            conf: conf.clone(),
+            client: Self::client(conf)?,
+        };
+        if !conf.user.is_empty() {
+            session.login()?;
+        }
+        Ok(session)
+    }
+
+    /// build client with the configured cookies
+    fn client(conf: &Config) -> Result<reqwest::Client> {
+        let mut headers = header::HeaderMap::new();
+        if !conf.cookies.is_empty() {
+            headers.insert(
+                header::COOKIE,
+                header::HeaderValue::from_str(&conf.cookies).map_err(|err| {
+                    Error::with_source(ErrorKind::NotParsable(String::from("cookies")), err)
+                })?,
+            );
+        }
+        Ok(reqwest::Client::builder()
+            .default_headers(headers)
+            .cookie_store(true)
+            .build()?)
+    }
+
+    /// login with school id and credentials, starts a new session
+    pub fn login(&mut self) -> Result<()> {
+        if self.conf.user.is_empty() {
+            return Err(Error::new_auth_expired(
+                "planinfo: session expired and no user set".to_string(),
+            ));
+        }
+        if self.conf.verbose >= 2 {
+            println!("Debug2: PlanInfo: login as {}", self.conf.user);
+        }
+        self.client = Self::client(&self.conf)?;
+        let form = [
+            ("ug", self.conf.school_id.as_str()),
+            (self.conf.user_field.as_str(), self.conf.user.as_str()),
+            (
+                self.conf.password_field.as_str(),
+                self.conf.password.as_str(),
+            ),
+        ];
+        let mut res = self
+            .client
+            .post(&format!(
+                "{}?ug={}",
+                self.conf.login_url, self.conf.school_id
+            ))
+            .form(&form)
+            .send()
+            .context("planinfo login")?;
+        if !res.status().is_success() {
+            return Err(Error::new_upstream_status(res.status().as_u16()).context("planinfo login"));
+        }
+        if is_login_page(&res.text()?) {
+            return Err(Error::new_auth_failed(
+                "planinfo: login rejected".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// get page of dbidx, logs in again once if the session expired
+    /// returns None if the server answers with an error status, fails if it limits the rate
+    pub fn get(&mut self, dbidx: i64) -> Result<Option<String>> {
+        let mut relogin = true;
+        loop {
+            let mut res = self
+                .client
+                .get(&format!(
+                    "{}?ug={}&dbidx={}",
+                    self.conf.base_url, self.conf.school_id, dbidx
+                ))
+                .send()
+                .with_context(|| format!("dbidx {}", dbidx))?;
+            if !res.status().is_success() {
+                let err = Error::new_upstream_status(res.status().as_u16())
+                    .context(format!("dbidx {}", dbidx));
+                if err.kind() == ErrorKind::RateLimited {
+                    return Err(err);
+                }
+                eprintln!("Error: PlanInfo: GET: {}", err);
+                return Ok(None);
+            }
+            let body = res.text().with_context(|| format!("dbidx {}", dbidx))?;
+            if !is_login_page(&body) {
+                return Ok(Some(body));
+            }
+            if !relogin {
+                return Err(Error::new_auth_expired(
+                    "planinfo: session expired after login".to_string(),
+                ));
+            }
+            if self.conf.verbose >= 1 {
+                println!("Debug1: PlanInfo: session expired at dbidx {}", dbidx);
+            }
+            self.login()?;
+            relogin = false;
+        }
+    }
+}
+
+/// test if page is the login form
+pub fn is_login_page(body: &str) -> bool {
+    body.to_lowercase().contains("type=\"password\"")
+}
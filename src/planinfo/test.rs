@@ -0,0 +1,453 @@
+//! test file for the planinfo parser
+
+use super::PlanInfo;
+use crate::common::Course;
+
+/// build planinfo page with one row per week
+fn page(title: &str, row: &str) -> String {
+    let mut ret = String::from(
+        "<!DOCTYPE html>\n<html><head><title>SLP-PlanInfo | Anzeige</title></head>\n<body>\n<div class=\"plan\">\n<table>\n",
+    );
+    for week in ["A", "B"].iter() {
+        ret += &format!(
+            "<thead><tr><th class=\"titel\" colspan=\"6\">{}-Woche-Stundenplan von {}</th></tr></thead>\n",
+            week, title
+        );
+        ret += &format!(
+            "<tbody><tr class=\"plan\"><th class=\"std\">1</th>{}</tr></tbody>\n",
+            row
+        );
+    }
+    ret += "</table>\n</div>\n</body></html>";
+    ret
+}
+
+#[test]
+fn parse_student() {
+    let html = String::from_utf8_lossy(include_bytes!("planinfo.html"));
+    let mut planinfo = PlanInfo::new();
+    let (table, kind) = planinfo
+        .parse_str(&format!("<!DOCTYPE html>\n{}", html))
+        .unwrap();
+    assert_eq!(kind, "students");
+    assert_eq!(planinfo.students.len(), 1);
+    assert_eq!(table.table_a[0][0].teacher.name, "MEB");
+    assert_eq!(table.table_a[0][0].room.to_string(), "A212");
+}
+
+#[test]
+fn no_body_text() {
+    // without text between head and body the html element has only two children
+    let html = "<!DOCTYPE html>\n<html><head><title>SLP-PlanInfo | Anzeige</title></head><body></body></html>";
+    let mut planinfo = PlanInfo::new();
+    assert!(planinfo.parse_str(html).is_err());
+}
+
+#[test]
+fn parse_class() {
+    let html = page(
+        "Klasse 5a",
+        "<td class=\"plan\">D MUE A114</td><td class=\"plan\">SP HEGG</td><td class=\"plan\"></td>",
+    );
+    let mut planinfo = PlanInfo::new();
+    let (table, kind) = planinfo.parse_str(&html).unwrap();
+    assert_eq!(kind, "classes");
+    assert_eq!(planinfo.classes.len(), 1);
+    assert_eq!(table.name, "5a");
+
+    let hour = &table.table_a[0][0];
+    match &hour.course {
+        Course::Sec1 { name } => assert_eq!(name, "D 5a"),
+        course => panic!("wrong course {:?}", course),
+    }
+    assert_eq!(hour.teacher.name, "MUE");
+    assert_eq!(hour.room.to_string(), "A114");
+
+    let hour = &table.table_b[1][0];
+    assert_eq!(hour.teacher.name, "HEGG");
+    assert!(table.table_b[2][0].is_empty());
+}
+
+#[test]
+fn student_courses() {
+    let html = String::from_utf8_lossy(include_bytes!("planinfo.html"));
+    let mut planinfo = PlanInfo::new();
+    let (table, _) = planinfo
+        .parse_str(&format!("<!DOCTYPE html>\n{}", html))
+        .unwrap();
+
+    let tutor = &table.courses[0];
+    assert!(tutor.is_tutor);
+    assert_eq!(tutor.course.to_string(), "EFk");
+    assert_eq!(tutor.teacher.name, "FOM");
+
+    assert!(table.attends("RK GK1", Some("MEB")));
+    assert!(table.attends("ph gk3", None));
+    assert!(!table.attends("RK GK1", Some("FOM")));
+    // same course in two rooms is one enrolment
+    let courses = table.courses.iter().filter(|v| v.teacher.name == "DOHE");
+    assert_eq!(courses.count(), 1);
+}
+
+#[test]
+fn enrolment_list() {
+    let courses = super::Enrolment::parse_list("EFk-FOM, D-GK11-HERM");
+    assert_eq!(courses.len(), 2);
+    assert!(courses[0].is_tutor);
+    assert!(!courses[1].is_tutor);
+    assert_eq!(courses[1].course.to_string(), "D GK11");
+    assert_eq!(courses[1].teacher.name, "HERM");
+}
+
+#[test]
+fn header_dimensions() {
+    let html = String::from_utf8_lossy(include_bytes!("planinfo.html"));
+    let mut planinfo = PlanInfo::new();
+    let (table, _) = planinfo
+        .parse_str(&format!("<!DOCTYPE html>\n{}", html))
+        .unwrap();
+    assert_eq!(
+        table.days,
+        vec!["Montag", "Dienstag", "Mittwoch", "Donnerstag", "Freitag"]
+    );
+    assert_eq!(table.periods.len(), 12);
+    assert_eq!(table.periods[11], "12");
+    assert_eq!(table.table_a.len(), 5);
+    assert_eq!(table.table_b[4].len(), 12);
+}
+
+#[test]
+fn saturday_and_more_periods() {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html><head><title>SLP-PlanInfo | Anzeige</title></head>\n<body>\n<div class=\"plan\">\n<table>\n",
+    );
+    for week in ["A", "B"].iter() {
+        html += &format!(
+            "<thead><tr><th class=\"titel\" colspan=\"7\">{}-Woche-Stundenplan von Klasse 5a</th></tr>\n",
+            week
+        );
+        html += "<tr><th class=\"std\">S\\T</th>";
+        for day in ["Mo", "Di", "Mi", "Do", "Fr", "Sa"].iter() {
+            html += &format!("<th class=\"wotag\">{}</th>", day);
+        }
+        html += "</tr></thead>\n<tbody>";
+        for period in 1..14 {
+            html += &format!("<tr class=\"plan\"><th class=\"std\">{}</th>", period);
+            for _ in 0..5 {
+                html += "<td class=\"plan\"></td>";
+            }
+            html += "<td class=\"plan\">D MUE A114</td></tr>";
+        }
+        html += "</tbody>\n";
+    }
+    html += "</table>\n</div>\n</body></html>";
+
+    let mut planinfo = PlanInfo::new();
+    let (table, _) = planinfo.parse_str(&html).unwrap();
+    assert_eq!(table.days.len(), 6);
+    assert_eq!(table.periods.len(), 13);
+    assert_eq!(table.table_a[5][12].teacher.name, "MUE");
+    assert_eq!(table.table_b[5][12].room.to_string(), "A114");
+    assert!(table.table_a[0][12].is_empty());
+}
+
+#[test]
+fn deserialize_fixed_table() {
+    // documents stored before the tables had labels
+    let hour = serde_json::to_value(crate::common::Hour::new()).unwrap();
+    let week = serde_json::json!(vec![vec![hour; 12]; 5]);
+    let table: super::Table = serde_json::from_value(serde_json::json!({
+        "name": "MUE",
+        "table_a": week,
+        "table_b": week,
+    }))
+    .unwrap();
+    assert_eq!(table.table_a.len(), 5);
+    assert_eq!(table.table_a[4].len(), 12);
+    assert!(table.days.is_empty());
+    assert!(table.courses.is_empty());
+}
+
+mod crawl {
+    use super::super::crawl::Crawl;
+    use super::super::{visit, PlanInfo};
+    use super::page;
+    use crate::error::ErrorKind;
+    use chrono::NaiveDate;
+
+    fn now() -> chrono::NaiveDateTime {
+        NaiveDate::from_ymd(2019, 7, 11).and_hms(8, 0, 0)
+    }
+
+    /// visit indices until the crawl is done, `valid` indices have tables
+    fn run(crawl: &mut Crawl, start: usize, end: usize, valid: &[i64]) -> Vec<i64> {
+        let mut visited = Vec::new();
+        crawl.begin(start, now());
+        while let Some(dbidx) = crawl.next(start, end, 3) {
+            if valid.contains(&dbidx) {
+                crawl.hit(dbidx, "students", &format!("{}", dbidx), now());
+            } else {
+                crawl.miss(dbidx);
+            }
+            crawl.position = dbidx;
+            visited.push(dbidx);
+        }
+        crawl.finish();
+        visited
+    }
+
+    #[test]
+    fn discover() {
+        let mut crawl = Crawl::new();
+        let visited = run(&mut crawl, 0, 0, &[1, 2, 4, 8]);
+        // stops after 3 misses beyond the last hit, 8 is not found
+        assert_eq!(visited, vec![1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(crawl.last_known(), Some(4));
+        assert!(crawl.is_known("students", "2"));
+        assert!(crawl.started.is_none());
+    }
+
+    #[test]
+    fn incremental() {
+        let mut crawl = Crawl::new();
+        run(&mut crawl, 0, 0, &[1, 2, 4]);
+        // 2 is gone, 6 is new
+        let visited = run(&mut crawl, 0, 0, &[1, 4, 6]);
+        assert_eq!(visited, vec![1, 2, 4, 5, 6, 7, 8, 9]);
+        let known: Vec<i64> = crawl.known.iter().map(|v| v.dbidx).collect();
+        assert_eq!(known, vec![1, 4, 6]);
+    }
+
+    #[test]
+    fn resume() {
+        let mut crawl = Crawl::new();
+        run(&mut crawl, 0, 0, &[1, 2, 4]);
+
+        // crawl stopped after visiting 2
+        assert!(crawl.begin(0, now()));
+        crawl.position = 2;
+        assert!(!crawl.begin(0, now()));
+        assert_eq!(crawl.next(0, 0, 3), Some(4));
+    }
+
+    #[test]
+    fn layout_changed() {
+        let mut crawl = Crawl::new();
+        run(&mut crawl, 0, 0, &[1, 2, 4]);
+        let mut planinfo = PlanInfo::new();
+
+        // a redesigned page stops the crawl without forgetting dbidx 2
+        let html = "<!DOCTYPE html>\n<html><head><title>SLP-PlanInfo | Anzeige</title></head>\n<body>\n<div class=\"neu\"></div>\n</body></html>";
+        let err = visit(&mut planinfo, &mut crawl, 2, Some(html.to_string()), now()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::LayoutChanged(_)));
+        assert!(crawl.is_known("students", "2"));
+
+        // so does the login page
+        let html =
+            "<!DOCTYPE html>\n<html><head><title>Login</title></head>\n<body>\n</body></html>";
+        let err = visit(&mut planinfo, &mut crawl, 2, Some(html.to_string()), now()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::AuthExpired(_)));
+        assert!(crawl.is_known("students", "2"));
+
+        // a missing page is forgotten
+        assert!(visit(&mut planinfo, &mut crawl, 2, None, now())
+            .unwrap()
+            .is_none());
+        assert!(!crawl.is_known("students", "2"));
+    }
+
+    #[test]
+    fn empty_plan() {
+        let mut crawl = Crawl::new();
+        run(&mut crawl, 0, 0, &[1, 2, 4]);
+        let mut planinfo = PlanInfo::new();
+        let html = page("", "<td></td>");
+        assert!(visit(&mut planinfo, &mut crawl, 2, Some(html), now())
+            .unwrap()
+            .is_none());
+        assert!(!crawl.is_known("students", "2"));
+
+        let html = page("Klasse 5a", "<td class=\"plan\">D MUE A114</td>");
+        let (table, kind) = visit(&mut planinfo, &mut crawl, 2, Some(html), now())
+            .unwrap()
+            .unwrap();
+        assert!(crawl.is_known(&kind, &table.name));
+    }
+
+    #[test]
+    fn start_end() {
+        let mut crawl = Crawl::new();
+        let visited = run(&mut crawl, 10, 13, &[11, 12, 13, 14]);
+        assert_eq!(visited, vec![11, 12, 13]);
+    }
+}
+
+mod history {
+    use super::super::history::{diff, Change};
+    use super::super::{Table, Week};
+    use crate::common::{Course, Room};
+    use chrono::NaiveDate;
+
+    fn table() -> Table {
+        let mut table = Table::with_size(5, 12);
+        table.name = String::from("MUE");
+        let hour = &mut table.table_a[1][2];
+        hour.course = Course::Sec1 {
+            name: String::from("D 5a"),
+        };
+        hour.room = Room::from_dsb_str("A114");
+        hour.teacher.name = String::from("MUE");
+        table
+    }
+
+    #[test]
+    fn unchanged() {
+        let mut new = table();
+        new.version = 3;
+        assert!(diff(&table(), &new).is_empty());
+        assert!(new.same(&table()));
+    }
+
+    #[test]
+    fn room_change() {
+        let mut new = table();
+        new.table_a[1][2].room = Room::from_dsb_str("B012");
+        let changes = diff(&table(), &new);
+        assert_eq!(changes.len(), 1);
+        let change = &changes[0];
+        assert_eq!(change.week, Week::A);
+        assert_eq!(change.day, "tue");
+        assert_eq!(change.period, 3);
+        assert!(change.course.is_none());
+        assert!(change.teacher.is_none());
+        let room = change.room.as_ref().unwrap();
+        assert_eq!(room.old, "A114");
+        assert_eq!(room.new, "B012");
+        assert!(!new.same(&table()));
+    }
+
+    #[test]
+    fn added_period() {
+        let mut new = table();
+        new.resize(5, 13);
+        new.table_b[0][12].course = Course::Sec1 {
+            name: String::from("SP 5a"),
+        };
+        let changes = diff(&table(), &new);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].week, Week::B);
+        assert_eq!(changes[0].period, 13);
+        assert_eq!(changes[0].course.as_ref().unwrap().old, "");
+    }
+
+    #[test]
+    fn change_version() {
+        let mut new = table();
+        new.version = 2;
+        new.table_a[1][2].teacher.name = String::from("HEGG");
+        let date = NaiveDate::from_ymd(2019, 7, 11).and_hms(8, 0, 0);
+        let change = Change::new("teachers", &table(), &new, date);
+        assert_eq!(change.version, 2);
+        assert_eq!(change.name, "MUE");
+        assert_eq!(change.slots.len(), 1);
+    }
+}
+
+mod session {
+    use super::super::session::{is_login_page, Session};
+    use super::super::Config;
+    use hyper::rt::{Future, Stream};
+    use hyper::service::service_fn;
+    use hyper::{Body, Request, Response};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    const LOGIN: &str = "<form><input type=\"password\" name=\"passwort\"></form>";
+
+    /// mock planinfo, the session number in the cookie must match the current one
+    /// `expire` increases the session number after that many pages
+    fn mock(expire: usize) -> (String, Arc<AtomicUsize>) {
+        let logins = Arc::new(AtomicUsize::new(0));
+        let pages = Arc::new(AtomicUsize::new(0));
+        let ret = logins.clone();
+
+        let addr = ([127, 0, 0, 1], 0).into();
+        let server = hyper::Server::bind(&addr).serve(move || {
+            let logins = logins.clone();
+            let pages = pages.clone();
+            service_fn(move |req: Request<Body>| {
+                let logins = logins.clone();
+                let pages = pages.clone();
+                let cookie = req
+                    .headers()
+                    .get("cookie")
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or("")
+                    .to_string();
+                let login = req.uri().path() == "/login";
+                req.into_body().concat2().map(move |body| {
+                    let body = String::from_utf8_lossy(&body).to_string();
+                    if login {
+                        if body.contains("user=mue") && body.contains("passwort=secret") {
+                            let session = logins.fetch_add(1, Ordering::SeqCst) + 1;
+                            return Response::builder()
+                                .header("Set-Cookie", format!("session={}", session))
+                                .body(Body::from("ok"))
+                                .unwrap();
+                        }
+                        return Response::new(Body::from(LOGIN));
+                    }
+
+                    let session = format!("session={}", logins.load(Ordering::SeqCst));
+                    if cookie != session || pages.load(Ordering::SeqCst) >= expire {
+                        pages.store(0, Ordering::SeqCst);
+                        logins.fetch_add(1, Ordering::SeqCst);
+                        return Response::new(Body::from(LOGIN));
+                    }
+                    pages.fetch_add(1, Ordering::SeqCst);
+                    Response::new(Body::from("<title>SLP-PlanInfo | Anzeige</title>"))
+                })
+            })
+        });
+        let url = format!("http://{}", server.local_addr());
+        std::thread::spawn(move || hyper::rt::run(server.map_err(|_| ())));
+        (url, ret)
+    }
+
+    fn config(url: &str, password: &str) -> Config {
+        let mut conf = Config::new();
+        conf.base_url = format!("{}/plan", url);
+        conf.login_url = format!("{}/login", url);
+        conf.school_id = String::from("lev-llg");
+        conf.user = String::from("mue");
+        conf.password = password.to_string();
+        conf
+    }
+
+    #[test]
+    fn login_page() {
+        assert!(is_login_page(LOGIN));
+        assert!(!is_login_page("<title>SLP-PlanInfo | Anzeige</title>"));
+    }
+
+    #[test]
+    fn relogin() {
+        let (url, logins) = mock(2);
+        let mut session = Session::new(&config(&url, "secret")).unwrap();
+        for dbidx in 1..6 {
+            let body = session.get(dbidx).unwrap().unwrap();
+            assert!(body.contains("Anzeige"));
+        }
+        // expired after the 2nd and 4th page, the mock counts expired sessions too
+        assert_eq!(logins.load(Ordering::SeqCst), 5);
+    }
+
+    #[test]
+    fn wrong_password() {
+        let (url, _) = mock(10);
+        match Session::new(&config(&url, "wrong")) {
+            Err(err) => assert!(err.to_string().contains("login rejected")),
+            Ok(_) => panic!("login with wrong password"),
+        }
+    }
+}
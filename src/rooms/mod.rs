@@ -0,0 +1,114 @@
+use chrono::prelude::*;
+
+pub use super::common::{Hour, Room, RoomParser};
+pub use super::dsb::{Entry, EntryKind, Week, DSB};
+use super::planinfo::Table;
+
+// tests as sub module
+#[cfg(test)] // only add when running tests
+mod test;
+
+/// find the rooms free in period on date
+/// `tables` are the room tables, `week` the week of date (None if there is no school)
+/// rooms are taken by the timetable unless the dsb drops the lesson or moves it out,
+/// and by blocked rooms and lessons the dsb moves in
+/// the names of the tables are parsed with rooms
+pub fn free(
+    tables: &[Table],
+    date: NaiveDate,
+    week: Option<Week>,
+    period: i16,
+    dsb: Option<&DSB>,
+    building: Option<&str>,
+    rooms: &RoomParser,
+) -> Vec<Room> {
+    let mut ret = Vec::new();
+    if period < 1 {
+        return ret;
+    }
+
+    // no lessons for everyone in this period
+    let free_lessons = match dsb.and_then(|v| v.free_lessons.as_ref()) {
+        Some(time) => time.contains(period),
+        None => false,
+    };
+
+    for table in tables.iter() {
+        let room = rooms.parse(&table.name);
+        if room.is_none() || ret.iter().any(|v: &Room| v.same(&room)) {
+            continue;
+        }
+        if let Some(building) = building {
+            if !room.building.eq_ignore_ascii_case(building) {
+                continue;
+            }
+        }
+
+        if !free_lessons {
+            let hour = week
+                .as_ref()
+                .and_then(|week| table.day(date.weekday(), week))
+                .and_then(|day| day.get(period as usize - 1));
+            if let Some(hour) = hour {
+                let left = match dsb {
+                    Some(dsb) => dsb
+                        .entries
+                        .iter()
+                        .any(|v| is_lesson(v, hour, period) && leaves(v, &room)),
+                    None => false,
+                };
+                if !hour.is_empty() && !left {
+                    continue;
+                }
+            }
+        }
+
+        if let Some(dsb) = dsb {
+            if dsb.blocked_rooms.iter().any(|v| {
+                v.room.same(&room)
+                    && match &v.time {
+                        Some(time) => time.contains(period),
+                        None => true,
+                    }
+            }) {
+                continue;
+            }
+            if dsb.entries.iter().any(|v| {
+                v.room.same(&room)
+                    && v.time.contains(period)
+                    && !matches!(v.kind, EntryKind::Dropped)
+            }) {
+                continue;
+            }
+        }
+        ret.push(room);
+    }
+    ret
+}
+
+/// test if entry of the dsb is about the scheduled hour in period, matched by teacher or course
+fn is_lesson(entry: &Entry, hour: &Hour, period: i16) -> bool {
+    if !entry.time.contains(period) {
+        return false;
+    }
+    let teacher = hour.teacher.name.trim();
+    let same_teacher = !teacher.is_empty()
+        && [&entry.old_teacher, &entry.new_teacher]
+            .iter()
+            .any(|v| v.name.trim().eq_ignore_ascii_case(teacher));
+    let course = hour.course.to_string();
+    let same_course = !course.is_empty()
+        && [&entry.course, &entry.old_course]
+            .iter()
+            .any(|v| v.to_string() == course);
+    same_teacher || same_course
+}
+
+/// test if the lesson of entry is dropped or moved out of room
+fn leaves(entry: &Entry, room: &Room) -> bool {
+    match entry.kind {
+        EntryKind::Dropped => true,
+        EntryKind::Room | EntryKind::RoomSubstitution | EntryKind::Moved => !entry.room.same(room),
+        _ => false,
+    }
+}
@@ -0,0 +1,189 @@
+//! test file for the free room finder
+
+use super::{free, Room, RoomParser, Week, DSB};
+use crate::common::Course;
+use crate::dsb::{BlockedRoom, Duration, Entry, EntryKind};
+use crate::planinfo::Table;
+use chrono::NaiveDate;
+
+/// rooms A101, A102, B201, Aula, the A101 is taken by MUE on monday period 1 in the A week
+fn tables() -> Vec<Table> {
+    let mut ret = Vec::new();
+    for name in ["A101", "A102", "B201", "Aula"].iter() {
+        let mut table = Table::with_size(5, 12);
+        table.name = name.to_string();
+        ret.push(table);
+    }
+    ret[0].table_a[0][0].course = Course::Sec1 {
+        name: String::from("D 5a"),
+    };
+    ret[0].table_a[0][0].teacher.name = String::from("MUE");
+    ret
+}
+
+/// dsb with an entry of kind in period 1, taught by teacher, in room
+fn change(kind: EntryKind, teacher: &str, room: &str) -> DSB {
+    let mut dsb = DSB::new();
+    let mut entry = Entry::new();
+    entry.kind = kind;
+    entry.old_teacher.name = teacher.to_string();
+    entry.room = Room::from_dsb_str(room);
+    entry.time = Duration { from: 1, to: 1 };
+    dsb.entries.push(entry);
+    dsb
+}
+
+fn monday_a1(dsb: &DSB) -> Vec<String> {
+    names(free(
+        &tables(),
+        monday(),
+        Some(Week::A),
+        1,
+        Some(dsb),
+        None,
+        &RoomParser::new(),
+    ))
+}
+
+fn monday() -> NaiveDate {
+    NaiveDate::from_ymd(2019, 7, 8)
+}
+
+fn names(rooms: Vec<Room>) -> Vec<String> {
+    rooms.iter().map(|v| v.to_string()).collect()
+}
+
+#[test]
+fn timetable() {
+    let rooms = free(
+        &tables(),
+        monday(),
+        Some(Week::A),
+        1,
+        None,
+        None,
+        &RoomParser::new(),
+    );
+    assert_eq!(names(rooms), vec!["A102", "B201", "Aula"]);
+    let rooms = free(
+        &tables(),
+        monday(),
+        Some(Week::B),
+        1,
+        None,
+        None,
+        &RoomParser::new(),
+    );
+    assert_eq!(names(rooms), vec!["A101", "A102", "B201", "Aula"]);
+    let rooms = free(
+        &tables(),
+        monday(),
+        Some(Week::A),
+        2,
+        None,
+        None,
+        &RoomParser::new(),
+    );
+    assert_eq!(rooms.len(), 4);
+}
+
+#[test]
+fn building() {
+    let rooms = free(
+        &tables(),
+        monday(),
+        Some(Week::A),
+        1,
+        None,
+        Some("b"),
+        &RoomParser::new(),
+    );
+    assert_eq!(names(rooms), vec!["B201"]);
+}
+
+#[test]
+fn dsb() {
+    let mut dsb = DSB::new();
+    dsb.blocked_rooms.push(BlockedRoom {
+        room: Room::from_dsb_str("A102"),
+        time: None,
+    });
+    let mut entry = Entry::new();
+    entry.kind = EntryKind::Room;
+    entry.room = Room::from_dsb_str("B201");
+    entry.time = Duration { from: 1, to: 2 };
+    dsb.entries.push(entry);
+
+    let rooms = free(
+        &tables(),
+        monday(),
+        Some(Week::B),
+        2,
+        Some(&dsb),
+        None,
+        &RoomParser::new(),
+    );
+    assert_eq!(names(rooms), vec!["A101", "Aula"]);
+    let rooms = free(
+        &tables(),
+        monday(),
+        Some(Week::B),
+        3,
+        Some(&dsb),
+        None,
+        &RoomParser::new(),
+    );
+    assert_eq!(names(rooms), vec!["A101", "B201", "Aula"]);
+}
+
+#[test]
+fn free_lessons() {
+    let mut dsb = DSB::new();
+    dsb.free_lessons = Some(Duration { from: 1, to: 6 });
+    let rooms = free(
+        &tables(),
+        monday(),
+        Some(Week::A),
+        1,
+        Some(&dsb),
+        None,
+        &RoomParser::new(),
+    );
+    assert_eq!(rooms.len(), 4);
+}
+
+#[test]
+fn dropped() {
+    let dsb = change(EntryKind::Dropped, "MUE", "A101");
+    assert_eq!(monday_a1(&dsb), vec!["A101", "A102", "B201", "Aula"]);
+
+    // the lesson is found by its course too
+    let mut dsb = change(EntryKind::Dropped, "", "");
+    dsb.entries[0].course = Course::Sec1 {
+        name: String::from("D 5a"),
+    };
+    assert_eq!(monday_a1(&dsb), vec!["A101", "A102", "B201", "Aula"]);
+
+    // a lesson of another teacher is dropped
+    let dsb = change(EntryKind::Dropped, "OTT", "A101");
+    assert_eq!(monday_a1(&dsb), vec!["A102", "B201", "Aula"]);
+}
+
+#[test]
+fn moved() {
+    // the lesson moves from A101 to B201
+    let dsb = change(EntryKind::Room, "MUE", "B201");
+    assert_eq!(monday_a1(&dsb), vec!["A101", "A102", "Aula"]);
+
+    // the lesson stays in its room
+    let dsb = change(EntryKind::RoomSubstitution, "MUE", "A101");
+    assert_eq!(monday_a1(&dsb), vec!["A102", "B201", "Aula"]);
+
+    // the lesson moves to another time
+    let dsb = change(EntryKind::Moved, "mue", "");
+    assert_eq!(monday_a1(&dsb), vec!["A101", "A102", "B201", "Aula"]);
+
+    // a substitution keeps the room
+    let dsb = change(EntryKind::Substitution, "MUE", "");
+    assert_eq!(monday_a1(&dsb), vec!["A102", "B201", "Aula"]);
+}
@@ -1,19 +1,558 @@
+use std::collections::BTreeMap;
+
+use chrono::prelude::*;
+use hyper::rt::Future;
+use hyper::service::service_fn_ok;
+use hyper::{Body, Request, Response, StatusCode};
+use serde::Serialize;
+
+pub use crate::error::Error;
+use crate::error::ErrorKind;
 #[doc(inline)]
 pub use crate::error::Result;
 
+use super::bells::{self, Lesson};
+use super::calendar::{self, ical};
+use super::common::RoomParser;
+use super::dsb::duty::Duty;
+use super::dsb::Week;
+use super::exams::{self, Exam};
+use super::index;
+use super::planinfo::Table;
+use super::rooms;
+use super::source;
+use super::storage::MongoDB;
+use super::teachers;
+
 /// struct holding server config
-pub struct Server {}
+#[derive(Clone)]
+pub struct Server {
+    /// address to listen on
+    address: String,
+
+    /// port to listen on
+    port: u16,
+
+    /// verbose level
+    verbose: u8,
+
+    /// storage backend
+    db: MongoDB,
+
+    /// bell schedules
+    bells: bells::Config,
+
+    /// school calendar
+    calendar: calendar::Config,
+
+    /// parser for the rooms of the school
+    room_parser: RoomParser,
+
+    /// teacher directory applied to the served tables and plans
+    teachers: teachers::Config,
+
+    /// states of the sources
+    sources: source::Status,
+}
+
+/// lessons of a day
+#[derive(Serialize)]
+struct Day {
+    date: NaiveDate,
+    week: Option<Week>,
+    lessons: Vec<Lesson>,
+    /// break supervisions, only for teachers
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    duties: Vec<Shift>,
+}
+
+/// duty with the times of its break
+#[derive(Serialize)]
+struct Shift {
+    #[serde(flatten)]
+    duty: Duty,
+    /// end of the period before the break, None before the first period
+    start: Option<NaiveDateTime>,
+    /// start of the period after the break, None after the last period
+    end: Option<NaiveDateTime>,
+}
+
+/// lessons running now
+#[derive(Serialize)]
+struct Now {
+    time: NaiveDateTime,
+    current: Option<Lesson>,
+    next: Option<Lesson>,
+}
 
 impl Server {
     /// create new instance
-    pub fn new(_conf: &super::Config) -> Self {
-        Self {}
+    pub fn new(
+        conf: &super::Config,
+        db: MongoDB,
+        calendar: calendar::Config,
+        teachers: teachers::Config,
+        sources: source::Status,
+    ) -> Self {
+        Self {
+            address: conf.address.clone(),
+            port: conf.port,
+            verbose: conf.verbose,
+            db,
+            bells: conf.bells.clone(),
+            calendar,
+            room_parser: conf.rooms.clone(),
+            teachers,
+            sources,
+        }
     }
+
     /// start server
     pub fn run(&self) -> Result<()> {
-        loop {
-            std::thread::sleep(std::time::Duration::from_secs(999999));
-        }
+        let addr: std::net::SocketAddr = format!("{}:{}", self.address, self.port)
+            .parse()
+            .map_err(|err: std::net::AddrParseError| {
+                Error::with_source(ErrorKind::NotParsable(String::from("address")), err)
+            })?;
+
+        let server = self.clone();
+        let http = hyper::Server::bind(&addr)
+            .serve(move || {
+                let server = server.clone();
+                service_fn_ok(move |req| server.handle(&req))
+            })
+            .map_err(|err| eprintln!("Error: Server: {}", err));
+
+        hyper::rt::run(http);
         Ok(())
     }
+
+    /// route request to the handlers
+    fn handle(&self, req: &Request<Body>) -> Response<Body> {
+        if self.verbose >= 2 {
+            println!("Debug2: Server: {} {}", req.method(), req.uri());
+        }
+        if req.method() != hyper::Method::GET {
+            return status(StatusCode::METHOD_NOT_ALLOWED);
+        }
+
+        let path: Vec<String> = req
+            .uri()
+            .path()
+            .trim_matches('/')
+            .split('/')
+            .map(|v| {
+                url::percent_encoding::percent_decode(v.as_bytes())
+                    .decode_utf8_lossy()
+                    .to_string()
+            })
+            .collect();
+        let path: Vec<&str> = path.iter().map(|v| v.as_str()).collect();
+        let query = query(req);
+
+        let ret = match path.as_slice() {
+            ["bells"] => Ok(json(&self.bells)),
+            ["status"] => self.status(),
+            ["timetable", kind, name] => self.timetable(kind, name, &query),
+            ["now", kind, name] => self.now(kind, name),
+            ["calendar", kind, name] => self.calendar(kind, name.trim_end_matches(".ics")),
+            ["courses", kind, name] => self.courses(kind, name),
+            ["history", kind, name] => self.history(kind, name),
+            ["students"] => self.students(&query),
+            ["dsb"] => self.dsb(&query),
+            ["exams"] => self.exams(&query),
+            ["exams", kind, name] => self.table_exams(kind, name),
+            ["rooms", "free"] => self.free_rooms(&query),
+            ["index"] => self.index(None, &query),
+            ["index", field] => self.index(Some(field), &query),
+            _ => Ok(status(StatusCode::NOT_FOUND)),
+        };
+        match ret {
+            Ok(ret) => ret,
+            Err(err) => {
+                eprintln!("Error: Server: {}: {}", req.uri(), err);
+                status(error_status(&err.kind()))
+            }
+        }
+    }
+
+    /// lessons of a table on a date, defaults to today
+    fn timetable(
+        &self,
+        kind: &str,
+        name: &str,
+        query: &BTreeMap<String, String>,
+    ) -> Result<Response<Body>> {
+        let date = match query.get("date") {
+            Some(date) => match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+                Ok(date) => date,
+                Err(_) => return Ok(status(StatusCode::BAD_REQUEST)),
+            },
+            None => Local::today().naive_local(),
+        };
+        let table = match self.table(kind, name)? {
+            Some(table) => table,
+            None => return Ok(status(StatusCode::NOT_FOUND)),
+        };
+        let calendar = self.calendar.load(&self.db)?;
+        let mut day = self.day(&calendar, &table, date);
+        if collection(kind) == Some("teachers") {
+            day.duties = self.duties(&table.name, date)?;
+        }
+        Ok(json(&day))
+    }
+
+    /// lesson running now and the next lesson of a table
+    fn now(&self, kind: &str, name: &str) -> Result<Response<Body>> {
+        let table = match self.table(kind, name)? {
+            Some(table) => table,
+            None => return Ok(status(StatusCode::NOT_FOUND)),
+        };
+        let calendar = self.calendar.load(&self.db)?;
+        let time = Local::now().naive_local();
+        let day = self.day(&calendar, &table, time.date());
+
+        let current = day
+            .lessons
+            .iter()
+            .find(|v| v.start <= time && time < v.end)
+            .cloned();
+        let next = day.lessons.iter().find(|v| v.start > time).cloned();
+        Ok(json(&Now {
+            time,
+            current,
+            next,
+        }))
+    }
+
+    /// iCalendar export of the lessons of a table
+    fn calendar(&self, kind: &str, name: &str) -> Result<Response<Body>> {
+        let table = match self.table(kind, name)? {
+            Some(table) => table,
+            None => return Ok(status(StatusCode::NOT_FOUND)),
+        };
+        let calendar = self.calendar.load(&self.db)?;
+        let today = Local::today().naive_local();
+
+        let mut events = Vec::new();
+        for v in calendar.school_days(today, today + chrono::Duration::days(self.calendar.days)) {
+            for lesson in self.day(&calendar, &table, v.date).lessons {
+                let mut event = ical::Event::new(
+                    &format!(
+                        "{}-{}-{}-{}@llg_companion",
+                        kind, name, v.date, lesson.period
+                    ),
+                    lesson.start,
+                    lesson.end,
+                    &lesson.hour.course.to_string(),
+                );
+                event.location = lesson.hour.room.to_string();
+                event.description = lesson.hour.teacher.display_name();
+                events.push(event);
+            }
+        }
+        for exam in self.upcoming_exams(&table)? {
+            if let Some(event) = self.exam_event(&exam) {
+                events.push(event);
+            }
+        }
+
+        Ok(Response::builder()
+            .header("Content-Type", "text/calendar; charset=utf-8")
+            .body(Body::from(ical::write(name, &events)))
+            .unwrap())
+    }
+
+    /// course list of a table
+    fn courses(&self, kind: &str, name: &str) -> Result<Response<Body>> {
+        match self.table(kind, name)? {
+            Some(table) => Ok(json(&table.courses)),
+            None => Ok(status(StatusCode::NOT_FOUND)),
+        }
+    }
+
+    /// names of the students attending `course`, optional only with `teacher`
+    fn students(&self, query: &BTreeMap<String, String>) -> Result<Response<Body>> {
+        let course = match query.get("course") {
+            Some(course) => course,
+            None => return Ok(status(StatusCode::BAD_REQUEST)),
+        };
+        let teacher = query.get("teacher").map(|v| v.as_str());
+        let students: Vec<String> = self
+            .db
+            .planinfo_read_tables("students")?
+            .into_iter()
+            .filter(|v| v.attends(course, teacher))
+            .map(|v| v.name)
+            .collect();
+        Ok(json(&students))
+    }
+
+    /// upcoming exams by course (`Q1 D GK1`), optional only of `course` (`D GK1`)
+    fn exams(&self, query: &BTreeMap<String, String>) -> Result<Response<Body>> {
+        let mut calendar = exams::calendar(self.read_exams()?);
+        if let Some(course) = query.get("course") {
+            let course = course.trim().to_lowercase();
+            calendar.retain(|key, _| key.to_lowercase().ends_with(&course));
+        }
+        Ok(json(&calendar))
+    }
+
+    /// upcoming exams of a table
+    fn table_exams(&self, kind: &str, name: &str) -> Result<Response<Body>> {
+        match self.table(kind, name)? {
+            Some(table) => Ok(json(&self.upcoming_exams(&table)?)),
+            None => Ok(status(StatusCode::NOT_FOUND)),
+        }
+    }
+
+    /// read the upcoming exams concerning table
+    fn upcoming_exams(&self, table: &Table) -> Result<Vec<Exam>> {
+        let mut exams = self.read_exams()?;
+        exams.retain(|v| v.concerns(table));
+        Ok(exams)
+    }
+
+    /// read the upcoming exams with the teacher directory applied to the supervisors
+    fn read_exams(&self) -> Result<Vec<Exam>> {
+        let mut exams = self.db.exams_read(Local::today().naive_local())?;
+        for exam in exams.iter_mut() {
+            for v in exam.supervisors.iter_mut() {
+                self.teachers.apply(&mut v.teacher);
+            }
+        }
+        Ok(exams)
+    }
+
+    /// calendar event of an exam, None if the periods have no times
+    fn exam_event(&self, exam: &Exam) -> Option<ical::Event> {
+        let building = exam.rooms.first().and_then(|v| v.building());
+        let (start, end) = self.bells.times(exam.date, &exam.time, building)?;
+        let mut event = ical::Event::new(
+            &format!(
+                "exam-{}-{}@llg_companion",
+                exam.date,
+                exam.key().replace(' ', "-")
+            ),
+            start,
+            end,
+            &exam.course.to_string(),
+        );
+        let rooms: Vec<String> = exam.rooms.iter().map(|v| v.to_string()).collect();
+        event.location = rooms.join(", ");
+        let supervisors: Vec<String> = exam
+            .supervisors
+            .iter()
+            .map(|v| v.teacher.display_name())
+            .collect();
+        event.description = supervisors.join(", ");
+        Some(event)
+    }
+
+    /// substitution plan of `date`, defaults to today
+    fn dsb(&self, query: &BTreeMap<String, String>) -> Result<Response<Body>> {
+        let date = match query.get("date") {
+            Some(date) => match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+                Ok(date) => date,
+                Err(_) => return Ok(status(StatusCode::BAD_REQUEST)),
+            },
+            None => Local::today().naive_local(),
+        };
+        match self.db.dsb_read(date)? {
+            Some(mut dsb) => {
+                self.teachers.apply_dsb(&mut dsb);
+                Ok(json(&dsb))
+            }
+            None => Ok(status(StatusCode::NOT_FOUND)),
+        }
+    }
+
+    /// state of the sources after their last poll
+    fn status(&self) -> Result<Response<Body>> {
+        match self.sources.lock() {
+            Ok(sources) => Ok(json(&*sources)),
+            Err(err) => {
+                eprintln!("Error: Server: status: {}", err);
+                Ok(status(StatusCode::INTERNAL_SERVER_ERROR))
+            }
+        }
+    }
+
+    /// rooms free in `period` on `date` (default today), optional only in `building`
+    fn free_rooms(&self, query: &BTreeMap<String, String>) -> Result<Response<Body>> {
+        let date = match query.get("date") {
+            Some(date) => match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+                Ok(date) => date,
+                Err(_) => return Ok(status(StatusCode::BAD_REQUEST)),
+            },
+            None => Local::today().naive_local(),
+        };
+        let period: i16 = match query.get("period").map(|v| v.parse()) {
+            Some(Ok(period)) if period > 0 => period,
+            _ => return Ok(status(StatusCode::BAD_REQUEST)),
+        };
+        let building = query.get("building").map(|v| v.as_str());
+
+        let calendar = self.calendar.load(&self.db)?;
+        let tables = self.db.planinfo_read_tables("room")?;
+        let dsb = self.db.dsb_read(date)?;
+        let rooms = rooms::free(
+            &tables,
+            date,
+            calendar.week(date),
+            period,
+            dsb.as_ref(),
+            building,
+            &self.room_parser,
+        );
+        let rooms: Vec<String> = rooms.iter().map(|v| v.to_string()).collect();
+        Ok(json(&rooms))
+    }
+
+    /// entries of the index matching the query parameters
+    /// with field only the distinct values of the field (`name`, `teacher`, ...)
+    fn index(
+        &self,
+        field: Option<&str>,
+        query: &BTreeMap<String, String>,
+    ) -> Result<Response<Body>> {
+        let query = match index::Query::from_params(query) {
+            Some(query) => query,
+            None => return Ok(status(StatusCode::BAD_REQUEST)),
+        };
+        let entries = self.db.index_find(&query)?;
+        let field = match field {
+            Some(field) => field,
+            None => return Ok(json(&entries)),
+        };
+
+        let mut values = std::collections::BTreeSet::new();
+        for v in entries.into_iter() {
+            values.insert(match field {
+                "kind" => v.kind,
+                "name" => v.name,
+                "course" => v.course,
+                "teacher" => v.teacher,
+                "room" => v.room,
+                "day" => v.day,
+                _ => return Ok(status(StatusCode::NOT_FOUND)),
+            });
+        }
+        Ok(json(&values))
+    }
+
+    /// get lessons of table on date
+    fn day(&self, calendar: &calendar::Calendar, table: &Table, date: NaiveDate) -> Day {
+        let lessons = match calendar.table_day(table, date) {
+            Some(hours) => self.bells.lessons(date, hours),
+            None => Vec::new(),
+        };
+        Day {
+            date,
+            week: calendar.week(date),
+            lessons,
+            duties: Vec::new(),
+        }
+    }
+
+    /// break supervisions of teacher on date with their times
+    fn duties(&self, teacher: &str, date: NaiveDate) -> Result<Vec<Shift>> {
+        let dsb = match self.db.teacher_dsb_read(date)? {
+            Some(dsb) => dsb,
+            None => return Ok(Vec::new()),
+        };
+        let schedule = self.bells.schedule(date.weekday(), None);
+        let mut ret = Vec::new();
+        for mut duty in dsb.duties.into_iter().filter(|v| v.concerns(teacher)) {
+            self.teachers.apply(&mut duty.teacher);
+            if let Some(substitute) = duty.substitute.as_mut() {
+                self.teachers.apply(substitute);
+            }
+            let start = schedule
+                .and_then(|v| v.period(duty.slot.before - 1))
+                .map(|v| date.and_time(v.end));
+            let end = schedule
+                .and_then(|v| v.period(duty.slot.before))
+                .map(|v| date.and_time(v.start));
+            ret.push(Shift { duty, start, end });
+        }
+        ret.sort_by_key(|v| v.duty.slot.before);
+        Ok(ret)
+    }
+
+    /// read table of kind (`teacher`, `room`, `student`, `class`) from storage
+    /// with the teachers of the directory applied
+    fn table(&self, kind: &str, name: &str) -> Result<Option<Table>> {
+        let mut table = match collection(kind) {
+            Some(collection) => self.db.planinfo_read_table(collection, name)?,
+            None => None,
+        };
+        if let Some(table) = table.as_mut() {
+            self.teachers.apply_table(table);
+        }
+        Ok(table)
+    }
+
+    /// changes of a table, newest first
+    fn history(&self, kind: &str, name: &str) -> Result<Response<Body>> {
+        match collection(kind) {
+            Some(collection) => Ok(json(&self.db.changes_read(collection, name)?)),
+            None => Ok(status(StatusCode::NOT_FOUND)),
+        }
+    }
+}
+
+/// get the collection of the tables of kind
+fn collection(kind: &str) -> Option<&'static str> {
+    match kind {
+        "teacher" => Some("teachers"),
+        "room" => Some("room"),
+        "student" => Some("students"),
+        "class" => Some("classes"),
+        _ => None,
+    }
+}
+
+/// parse query string of request
+fn query(req: &Request<Body>) -> BTreeMap<String, String> {
+    let query = req.uri().query().unwrap_or("");
+    url::form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect()
+}
+
+/// create json response
+fn json<T: Serialize>(value: &T) -> Response<Body> {
+    match serde_json::to_string(value) {
+        Ok(body) => Response::builder()
+            .header("Content-Type", "application/json")
+            .body(Body::from(body))
+            .unwrap(),
+        Err(err) => {
+            eprintln!("Error: Server: json: {}", err);
+            status(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// status code answering a request failed with an error of kind
+fn error_status(kind: &ErrorKind) -> StatusCode {
+    match kind {
+        ErrorKind::AuthFailed(_)
+        | ErrorKind::AuthExpired(_)
+        | ErrorKind::UpstreamStatus(_)
+        | ErrorKind::LayoutChanged(_)
+        | ErrorKind::Http => StatusCode::BAD_GATEWAY,
+        ErrorKind::RateLimited | ErrorKind::StorageUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+        ErrorKind::EmptyPlan(_) => StatusCode::NOT_FOUND,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// create empty response with status code
+fn status(code: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(code)
+        .body(Body::from(code.to_string()))
+        .unwrap()
 }
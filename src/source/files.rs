@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+
+use super::{Sink, Source};
+use crate::common::RoomParser;
+use crate::dsb::{self, EntryKind, DSB};
+use crate::error::Result;
+use crate::planinfo::{PlanInfo, Table};
+
+/// parsed content of the directory
+#[derive(Debug, Default)]
+pub struct Content {
+    /// days of the substitution pages
+    pub days: Vec<DSB>,
+    /// tables of the planinfo pages with their kind
+    pub tables: Vec<(Table, String)>,
+}
+
+/// config for a directory of html files
+/// Untis substitution pages and planinfo pages can be dropped into it
+#[derive(Clone)]
+pub struct Config {
+    /// directory to read
+    pub path: String,
+
+    /// time between two reads of the directory
+    pub interval: std::time::Duration,
+
+    /// parser for the rooms of the school
+    pub room_parser: RoomParser,
+
+    /// extra keywords of the kind column of the substitution pages
+    pub kinds: BTreeMap<String, EntryKind>,
+
+    /// verbose level
+    pub verbose: u8,
+}
+
+impl Config {
+    /// create a new instance of Config
+    pub fn new() -> Self {
+        Self {
+            path: String::new(),
+            interval: std::time::Duration::from_secs(300),
+            room_parser: RoomParser::new(),
+            kinds: BTreeMap::new(),
+            verbose: 0,
+        }
+    }
+
+    /// read and parse the html files of the directory, in order of their names
+    /// planinfo pages are returned as tables with their kind, all other pages as days
+    pub fn read(&self) -> Result<Content> {
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(&self.path)? {
+            let path = entry?.path();
+            let is_html = match path.extension().and_then(|v| v.to_str()) {
+                Some(ext) => ext.eq_ignore_ascii_case("htm") || ext.eq_ignore_ascii_case("html"),
+                None => false,
+            };
+            if is_html && path.is_file() {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        let mut dsb = dsb::Config::new();
+        dsb.verbose = self.verbose;
+        dsb.room_parser = self.room_parser.clone();
+        dsb.kinds = self.kinds.clone();
+        let mut planinfo = PlanInfo::new();
+        planinfo.room_parser = self.room_parser.clone();
+        let mut content = Content::default();
+        for path in paths.iter() {
+            if self.verbose >= 3 {
+                println!("Debug3: Files: read {}", path.display());
+            }
+            // Untis exports are often latin1
+            let html = String::from_utf8_lossy(&std::fs::read(path)?).to_string();
+            if let Ok(table) = planinfo.parse_str(&html) {
+                content.tables.push(table);
+                continue;
+            }
+            match dsb.parse(&html) {
+                Ok(mut v) => content.days.append(&mut v),
+                Err(err) => eprintln!("Error: Files: parse {}: {}", path.display(), err),
+            }
+        }
+        // pages of a monitor (`subst_001.htm`, ...) may split a day
+        content.days = DSB::merge(content.days);
+        Ok(content)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Source for Config {
+    fn name(&self) -> &str {
+        "Files"
+    }
+
+    fn interval(&self) -> std::time::Duration {
+        self.interval
+    }
+
+    fn poll(&mut self, sink: &mut Sink) -> Result<()> {
+        let content = self.read()?;
+        for day in content.days {
+            sink.day(day)?;
+        }
+        for (table, kind) in content.tables.iter() {
+            sink.timetable(table, kind)?;
+        }
+        // index only the tables of the drop directory, removed files drop their entries
+        let tables = &content.tables;
+        sink.rebuild_index(self.name(), |kind, name| {
+            tables
+                .iter()
+                .any(|(table, k)| k == kind && table.name == name)
+        })?;
+        Ok(())
+    }
+}
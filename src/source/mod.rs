@@ -0,0 +1,277 @@
+use chrono::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use super::calendar;
+use super::dsb::DSB;
+use super::error::{ErrorKind, Result};
+use super::planinfo::Table;
+use super::storage::MongoDB;
+
+/// directory of html files dropped by the school
+pub mod files;
+
+// tests as sub module
+#[cfg(test)] // only add when running tests
+mod test;
+
+/// source of substitution plans and timetables
+/// a source only loads and parses, the results are handed to the sink
+pub trait Source: Send {
+    /// name of the source used in logs
+    fn name(&self) -> &str;
+
+    /// time to wait between two polls
+    fn interval(&self) -> std::time::Duration;
+
+    /// test if the source should be polled on date
+    /// defaults to school days
+    fn is_due(&self, calendar: &calendar::Config, date: NaiveDate) -> bool {
+        calendar.is_school_day(date)
+    }
+
+    /// load the source and write the days and timetables to sink
+    fn poll(&mut self, sink: &mut Sink) -> Result<()>;
+}
+
+/// first delay to poll again after a transient error, doubles with every failed poll
+const RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// delay to poll again after the config was rejected, at least the interval of the source
+const PAUSE_DELAY: std::time::Duration = std::time::Duration::from_secs(6 * 3600);
+
+/// what the scheduler does after a failed poll
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Retry {
+    /// transient error, poll again after the retry delay
+    Soon,
+    /// the upstream limits the rate, wait twice the interval
+    Slower,
+    /// retrying does not help before the upstream changes, wait the interval
+    Interval,
+    /// the config has to be fixed, poll again after the pause delay
+    Paused,
+}
+
+/// retry decision after a poll failed with an error of kind
+pub fn retry(kind: &ErrorKind) -> Retry {
+    match kind {
+        ErrorKind::AuthFailed(_) => Retry::Paused,
+        ErrorKind::RateLimited => Retry::Slower,
+        ErrorKind::UpstreamStatus(status) if *status >= 500 => Retry::Soon,
+        ErrorKind::AuthExpired(_)
+        | ErrorKind::Http
+        | ErrorKind::StorageUnavailable
+        | ErrorKind::IoConnectionRefused
+        | ErrorKind::IoConnectionReset
+        | ErrorKind::IoConnectionAborted
+        | ErrorKind::IoNotConnected
+        | ErrorKind::IoBrokenPipe
+        | ErrorKind::IoTimedOut
+        | ErrorKind::IoInterrupted => Retry::Soon,
+        _ => Retry::Interval,
+    }
+}
+
+/// delay after failures polls failed in a row with transient errors, at most interval
+pub fn backoff(failures: u32, interval: std::time::Duration) -> std::time::Duration {
+    let factor = 2u32.saturating_pow(failures.saturating_sub(1));
+    RETRY_DELAY
+        .checked_mul(factor)
+        .unwrap_or(interval)
+        .min(interval)
+}
+
+/// state of a source after its last poll
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct State {
+    pub last_poll: NaiveDateTime,
+    pub last_success: Option<NaiveDateTime>,
+    /// error of the last poll, None if it succeeded
+    pub error: Option<String>,
+    /// decision after the error of the last poll
+    pub retry: Option<Retry>,
+    /// polls failed in a row
+    pub failures: u32,
+    pub next_poll: NaiveDateTime,
+}
+
+/// states of the sources by name, shared between the poll threads and the server
+pub type Status = Arc<Mutex<BTreeMap<String, State>>>;
+
+/// receives the normalized days and timetables of a source and stores them
+pub struct Sink {
+    db: MongoDB,
+    days: Vec<DSB>,
+    timetables: usize,
+}
+
+impl Sink {
+    /// create a new sink writing to db
+    pub fn new(db: MongoDB) -> Self {
+        Self {
+            db,
+            days: Vec::new(),
+            timetables: 0,
+        }
+    }
+
+    /// store the substitutions of a day and the exams found in them
+    pub fn day(&mut self, day: DSB) -> Result<()> {
+        self.db.dsb_write(&day)?;
+        self.db
+            .exams_write(day.date, &super::exams::collect(&day))?;
+        self.days.push(day);
+        Ok(())
+    }
+
+    /// store the teacher plan of a day next to the plan of the students
+    pub fn teacher_day(&mut self, day: DSB) -> Result<()> {
+        self.db.teacher_dsb_write(&day)
+    }
+
+    /// store a timetable of kind (`teachers`, `room`, `students`, `classes`)
+    pub fn timetable(&mut self, table: &Table, kind: &str) -> Result<()> {
+        self.db.planinfo_write_table(table, kind)?;
+        self.timetables += 1;
+        Ok(())
+    }
+
+    /// rebuild the index entries of source from the stored timetables matching filter (kind, name)
+    /// the entries of the other sources are kept
+    /// returns the number of index entries
+    pub fn rebuild_index<F: Fn(&str, &str) -> bool>(
+        &self,
+        source: &str,
+        filter: F,
+    ) -> Result<usize> {
+        let mut entries = Vec::new();
+        for kind in ["teachers", "room", "students", "classes"].iter() {
+            for table in self.db.planinfo_read_tables(kind)? {
+                if filter(kind, &table.name) {
+                    entries.append(&mut super::index::entries(kind, &table));
+                }
+            }
+        }
+        for v in entries.iter_mut() {
+            v.source = source.to_string();
+        }
+        self.db.index_write(source, &entries)?;
+        Ok(entries.len())
+    }
+
+    /// read the state of source, like a stopped crawl
+    pub fn state_read<T: DeserializeOwned>(&self, source: &str) -> Result<Option<T>> {
+        self.db.state_read(source)
+    }
+
+    /// store the state of source to resume it later, every source has its own state
+    pub fn state_write<T: Serialize>(&self, source: &str, state: &T) -> Result<()> {
+        self.db.state_write(source, state)
+    }
+
+    /// days stored since the sink was created
+    pub fn days(&self) -> &[DSB] {
+        &self.days
+    }
+
+    /// number of timetables stored since the sink was created
+    pub fn timetables(&self) -> usize {
+        self.timetables
+    }
+}
+
+/// poll source in a new thread, the state after every poll is written to status
+pub fn run(
+    source: Box<dyn Source>,
+    db: MongoDB,
+    calendar: calendar::Config,
+    status: Status,
+    verbose: u8,
+) {
+    std::thread::spawn(move || run_int(source, db, calendar, status, verbose));
+}
+
+/// internal run function holding the main loop of the thread
+fn run_int(
+    mut source: Box<dyn Source>,
+    db: MongoDB,
+    calendar: calendar::Config,
+    status: Status,
+    verbose: u8,
+) {
+    let mut failures = 0;
+    let mut last_success = None;
+    loop {
+        if !source.is_due(&calendar, Local::today().naive_local()) {
+            if verbose >= 3 {
+                println!("Debug3: {}: no school, skip polling", source.name());
+            }
+            std::thread::sleep(std::time::Duration::from_secs(3600)); // sleep for one hour
+            continue;
+        }
+
+        let mut sink = Sink::new(db.clone());
+        let last_poll = Local::now().naive_local();
+        let (decision, error) = match source.poll(&mut sink) {
+            Ok(()) => {
+                failures = 0;
+                last_success = Some(last_poll);
+                (None, None)
+            }
+            Err(err) => {
+                eprintln!("Error: {}: {}", source.name(), err);
+                failures += 1;
+                (Some(retry(&err.kind())), Some(err.to_string()))
+            }
+        };
+        if verbose >= 2 {
+            println!(
+                "Debug2: {}: stored {} days and {} timetables",
+                source.name(),
+                sink.days().len(),
+                sink.timetables()
+            );
+        }
+        if !sink.days().is_empty() {
+            if let Err(err) = calendar.update(&db, sink.days()) {
+                eprintln!("Error: {}: calendar: {}", source.name(), err);
+            }
+        }
+
+        let wait = match decision {
+            None | Some(Retry::Interval) => source.interval(),
+            Some(Retry::Soon) => backoff(failures, source.interval()),
+            Some(Retry::Slower) => source.interval() * 2,
+            Some(Retry::Paused) => {
+                eprintln!("Error: {}: paused, fix the config", source.name());
+                PAUSE_DELAY.max(source.interval())
+            }
+        };
+        let state = State {
+            last_poll,
+            last_success,
+            error,
+            retry: decision,
+            failures,
+            next_poll: last_poll
+                + chrono::Duration::from_std(wait).unwrap_or_else(|_| chrono::Duration::zero()),
+        };
+        match status.lock() {
+            Ok(mut status) => {
+                status.insert(source.name().to_string(), state);
+            }
+            Err(err) => eprintln!("Error: {}: status: {}", source.name(), err),
+        }
+        if verbose >= 2 && decision.is_some() {
+            println!(
+                "Debug2: {}: poll again in {} s",
+                source.name(),
+                wait.as_secs()
+            );
+        }
+        std::thread::sleep(wait);
+    }
+}
@@ -0,0 +1,116 @@
+//! test file for the sources
+
+mod files {
+    use super::super::files::Config;
+
+    /// create an empty directory for the test
+    fn dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("llg_files_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn config(dir: &std::path::Path) -> Config {
+        let mut conf = Config::new();
+        conf.path = dir.to_str().unwrap().to_string();
+        conf
+    }
+
+    #[test]
+    fn untis_and_planinfo() {
+        let dir = dir("mixed");
+        std::fs::write(
+            dir.join("subst_001.htm"),
+            &include_bytes!("../dsb/dsb.html")[..],
+        )
+        .unwrap();
+        let planinfo = format!(
+            "<!DOCTYPE html>\n{}",
+            include_str!("../planinfo/planinfo.html")
+        );
+        std::fs::write(dir.join("planinfo.html"), planinfo).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a plan").unwrap();
+
+        let content = config(&dir).read().unwrap();
+        assert!(!content.days.is_empty());
+        assert_eq!(content.tables.len(), 1);
+        assert_eq!(content.tables[0].1, "students");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn stray_html() {
+        let dir = dir("stray");
+        std::fs::write(
+            dir.join("subst_001.htm"),
+            &include_bytes!("../dsb/dsb.html")[..],
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("index.html"),
+            "<html><body><h1>Vertretungsplan</h1></body></html>",
+        )
+        .unwrap();
+
+        let content = config(&dir).read().unwrap();
+        assert!(!content.days.is_empty());
+        assert!(content.tables.is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn empty() {
+        let dir = dir("empty");
+        let content = config(&dir).read().unwrap();
+        assert!(content.days.is_empty());
+        assert!(content.tables.is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_dir() {
+        let mut conf = Config::new();
+        conf.path = String::from("/nonexistent/llg_files");
+        assert!(conf.read().is_err());
+    }
+}
+
+mod retry {
+    use super::super::{backoff, retry, Retry};
+    use crate::error::ErrorKind;
+    use std::time::Duration;
+
+    #[test]
+    fn decisions() {
+        assert_eq!(retry(&ErrorKind::AuthFailed(String::new())), Retry::Paused);
+        assert_eq!(retry(&ErrorKind::AuthExpired(String::new())), Retry::Soon);
+        assert_eq!(retry(&ErrorKind::RateLimited), Retry::Slower);
+        assert_eq!(retry(&ErrorKind::UpstreamStatus(503)), Retry::Soon);
+        assert_eq!(retry(&ErrorKind::UpstreamStatus(404)), Retry::Interval);
+        assert_eq!(retry(&ErrorKind::Http), Retry::Soon);
+        assert_eq!(retry(&ErrorKind::StorageUnavailable), Retry::Soon);
+        assert_eq!(retry(&ErrorKind::IoTimedOut), Retry::Soon);
+        assert_eq!(
+            retry(&ErrorKind::LayoutChanged(String::new())),
+            Retry::Interval
+        );
+        assert_eq!(retry(&ErrorKind::EmptyPlan(String::new())), Retry::Interval);
+    }
+
+    #[test]
+    fn backoff_doubles() {
+        let interval = Duration::from_secs(3600);
+        assert_eq!(backoff(1, interval), Duration::from_secs(60));
+        assert_eq!(backoff(2, interval), Duration::from_secs(120));
+        assert_eq!(backoff(3, interval), Duration::from_secs(240));
+    }
+
+    #[test]
+    fn backoff_capped() {
+        let interval = Duration::from_secs(300);
+        assert_eq!(backoff(4, interval), interval);
+        assert_eq!(backoff(100, interval), interval);
+        assert_eq!(backoff(1, Duration::from_secs(30)), Duration::from_secs(30));
+    }
+}
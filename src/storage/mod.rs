@@ -1,19 +1,67 @@
 use std::sync::Arc;
 
+use bson::{bson, doc};
 use mongodb::coll::results::InsertOneResult;
 use mongodb::db::ThreadedDatabase;
 use mongodb::Bson;
 use mongodb::Client;
 use mongodb::ThreadedClient;
 
+use serde::de::DeserializeOwned;
 use serde::Serialize;
 
+use super::error::Context;
 pub use super::error::Error;
 #[doc(inline)]
 pub use super::error::Result;
 
 pub use super::common::{Hour, Room, Teacher};
 
+// tests as sub module
+#[cfg(test)] // only add when running tests
+mod test;
+
+/// version of the document schema, raise it on incompatible changes of the stored types
+/// documents written before the schema was versioned have no version and are read as 0
+pub const SCHEMA_VERSION: i32 = 2;
+
+/// field holding the schema version in the documents
+const SCHEMA_FIELD: &str = "schema";
+
+/// encode value as document tagged with the schema version
+pub fn to_document<T: Serialize>(value: &T) -> Result<bson::Document> {
+    match mongodb::to_bson(value)? {
+        Bson::Document(mut doc) => {
+            doc.insert(SCHEMA_FIELD, SCHEMA_VERSION);
+            Ok(doc)
+        }
+        _ => Err(Error::new_field_not_exists("document".to_string())),
+    }
+}
+
+/// schema version of document, 0 if it has none
+fn schema_version(doc: &bson::Document) -> i32 {
+    match doc.get(SCHEMA_FIELD) {
+        Some(Bson::I32(version)) => *version,
+        Some(Bson::I64(version)) => *version as i32,
+        _ => 0,
+    }
+}
+
+/// decode document, documents of a newer schema version are rejected
+/// errors name the `_id` of the document
+pub fn from_document<T: DeserializeOwned>(doc: bson::Document) -> Result<T> {
+    let id = match doc.get("_id") {
+        Some(id) => format!("document {}", id),
+        None => String::from("document without id"),
+    };
+    let version = schema_version(&doc);
+    if version > SCHEMA_VERSION {
+        return Err(Error::new_schema_not_supported(version).context(id));
+    }
+    mongodb::from_bson(Bson::Document(doc)).context(id)
+}
+
 #[derive(Serialize)]
 pub struct Config {
     /// url to connect to cluster
@@ -24,6 +72,24 @@ pub struct Config {
 
     /// collection for dsb
     pub dsb_coll: String,
+
+    /// collection for the teacher plans of the dsb
+    pub teacher_dsb_coll: String,
+
+    /// collection for the school calendar
+    pub calendar_coll: String,
+
+    /// collection for the index over the planinfo tables
+    pub index_coll: String,
+
+    /// collection for the state of the sources, like the progress of the planinfo crawler
+    pub crawl_coll: String,
+
+    /// collection for the changes of the planinfo tables
+    pub changes_coll: String,
+
+    /// collection for the exams found in the dsb
+    pub exams_coll: String,
 }
 
 impl Config {
@@ -39,6 +105,12 @@ impl Config {
             client: client,
             database: self.database.clone(),
             dsb_collection: self.dsb_coll.clone(),
+            teacher_dsb_collection: self.teacher_dsb_coll.clone(),
+            calendar_collection: self.calendar_coll.clone(),
+            index_collection: self.index_coll.clone(),
+            crawl_collection: self.crawl_coll.clone(),
+            changes_collection: self.changes_coll.clone(),
+            exams_collection: self.exams_coll.clone(),
         }))
     }
 }
@@ -49,27 +121,111 @@ impl Default for Config {
             url: String::from("localhost:27017"),
             database: String::from("llg_companion"),
             dsb_coll: String::from("dsb"),
+            teacher_dsb_coll: String::from("teacher_dsb"),
+            calendar_coll: String::from("calendar"),
+            index_coll: String::from("index"),
+            crawl_coll: String::from("crawl"),
+            changes_coll: String::from("changes"),
+            exams_coll: String::from("exams"),
         }
     }
 }
 
-#[derive(Serialize)]
-struct dsb_update_field {
-    updated_at: chrono::NaiveDateTime,
+/// migrate a dsb document of schema 0 to the current shape
+/// the first versions stored `FreeLessons` as string, the missing teachers as teachers
+/// and the blocked rooms as rooms, and had no missing classes and messages
+/// later documents of schema 0 already have the current shape, so every field is checked
+fn migrate_dsb(mut doc: bson::Document) -> bson::Document {
+    if schema_version(&doc) > 0 {
+        return doc;
+    }
+    if let Some(free) = doc.remove("FreeLessons") {
+        let free = match free {
+            Bson::String(free) => super::dsb::Duration::parse_from_str(&free),
+            _ => None,
+        };
+        doc.insert(
+            "free_lessons",
+            mongodb::to_bson(&free).unwrap_or(Bson::Null),
+        );
+    }
+    wrap_items(&mut doc, "missing_teachers", "teacher");
+    wrap_items(&mut doc, "blocked_rooms", "room");
+    for field in ["free_lessons", "missing_classes", "messages"].iter() {
+        if !doc.contains_key(field) {
+            let empty = match *field {
+                "free_lessons" => Bson::Null,
+                _ => Bson::Array(Vec::new()),
+            };
+            doc.insert(*field, empty);
+        }
+    }
+    doc
 }
 
-impl dsb_update_field {
-    pub fn new(time: &chrono::NaiveDateTime) -> Self {
-        Self { updated_at: *time }
+/// wrap the items of the array field into `{ key: item, time: null }` if they have no key
+fn wrap_items(doc: &mut bson::Document, field: &str, key: &str) {
+    if let Some(Bson::Array(items)) = doc.get_mut(field) {
+        for v in items.iter_mut() {
+            let wrapped = match v {
+                Bson::Document(v) => v.contains_key(key),
+                _ => false,
+            };
+            if !wrapped {
+                let mut item = bson::Document::new();
+                item.insert(key, std::mem::replace(v, Bson::Null));
+                item.insert("time", Bson::Null);
+                *v = Bson::Document(item);
+            }
+        }
     }
 }
 
 pub type MongoDB = std::sync::Arc<MongoDBInner>;
 
+/// write dsb to collection if there is no dsb of the same date and update
+fn dsb_write_coll(coll: &mongodb::coll::Collection, document: &super::dsb::DSB) -> Result<()> {
+    let filter = doc! {
+        "updated_at": mongodb::to_bson(&document.updated_at)?,
+        "date": mongodb::to_bson(&document.date)?,
+    };
+    let dsb_in_cache = coll.find_one(Some(filter), None)?;
+    if dsb_in_cache.is_none() {
+        coll.insert_one(to_document(document)?, None)?;
+    }
+    Ok(())
+}
+
+/// read the newest dsb of date from collection
+fn dsb_read_coll(
+    coll: &mongodb::coll::Collection,
+    date: chrono::NaiveDate,
+) -> Result<Option<super::dsb::DSB>> {
+    let mut options = mongodb::coll::options::FindOptions::new();
+    options.sort = Some(doc! { "updated_at": -1 });
+    let context = || format!("{} {}", coll.name(), date);
+    let dsb = coll
+        .find_one(
+            Some(doc! { "date": mongodb::to_bson(&date)? }),
+            Some(options),
+        )
+        .with_context(context)?;
+    match dsb {
+        Some(dsb) => Ok(Some(from_document(migrate_dsb(dsb)).with_context(context)?)),
+        None => Ok(None),
+    }
+}
+
 pub struct MongoDBInner {
     client: Client,
     database: String,
     dsb_collection: String,
+    teacher_dsb_collection: String,
+    calendar_collection: String,
+    index_collection: String,
+    crawl_collection: String,
+    changes_collection: String,
+    exams_collection: String,
 }
 
 impl MongoDBInner {
@@ -85,36 +241,235 @@ impl MongoDBInner {
         self.db().collection(&self.dsb_collection)
     }
 
+    pub fn teacher_dsb_coll(&self) -> mongodb::coll::Collection {
+        self.db().collection(&self.teacher_dsb_collection)
+    }
+
+    pub fn calendar_coll(&self) -> mongodb::coll::Collection {
+        self.db().collection(&self.calendar_collection)
+    }
+
+    pub fn index_coll(&self) -> mongodb::coll::Collection {
+        self.db().collection(&self.index_collection)
+    }
+
+    pub fn crawl_coll(&self) -> mongodb::coll::Collection {
+        self.db().collection(&self.crawl_collection)
+    }
+
+    pub fn changes_coll(&self) -> mongodb::coll::Collection {
+        self.db().collection(&self.changes_collection)
+    }
+
+    pub fn exams_coll(&self) -> mongodb::coll::Collection {
+        self.db().collection(&self.exams_collection)
+    }
+
     pub fn dsb_write(&self, document: &super::dsb::DSB) -> Result<()> {
-        let bson = mongodb::to_bson(&dsb_update_field::new(&document.updated_at)).unwrap();
-        let bson = bson.as_document().unwrap();
-        let dsb_in_cache = self.dsb_coll().find_one(Some(bson.clone()), None).unwrap();
-        if dsb_in_cache == None {
-            let bson = mongodb::to_bson(document).unwrap();
-            let bson = bson.as_document().unwrap();
-            self.dsb_coll().insert_one(bson.clone(), None)?;
-        }
-        Ok(())
+        dsb_write_coll(&self.dsb_coll(), document)
     }
 
+    /// write a teacher plan, kept apart from the plans of the students
+    pub fn teacher_dsb_write(&self, document: &super::dsb::DSB) -> Result<()> {
+        dsb_write_coll(&self.teacher_dsb_coll(), document)
+    }
+
+    /// write table if it changed, the changes to the last version are stored as history
     pub fn planinfo_write_table(
         &self,
         table: &super::planinfo::Table,
         collection: &str,
     ) -> Result<()> {
-        let bson = mongodb::to_bson(table).unwrap();
-        let bson = bson.as_document().unwrap();
-        let in_cache = self
+        let mut table = table.clone();
+        if let Some(old) = self.planinfo_read_table(collection, &table.name)? {
+            if old.same(&table) {
+                return Ok(());
+            }
+            table.version = old.version + 1;
+            let now = chrono::Local::now().naive_local();
+            let change = super::planinfo::history::Change::new(collection, &old, &table, now);
+            if !change.slots.is_empty() {
+                self.changes_coll()
+                    .insert_one(to_document(&change)?, None)?;
+            }
+        }
+        self.db()
+            .collection(collection)
+            .insert_one(to_document(&table)?, None)?;
+        Ok(())
+    }
+
+    /// read the changes of table name of kind, newest first
+    pub fn changes_read(
+        &self,
+        kind: &str,
+        name: &str,
+    ) -> Result<Vec<super::planinfo::history::Change>> {
+        let mut options = mongodb::coll::options::FindOptions::new();
+        options.sort = Some(doc! { "version": -1 });
+        let mut ret = Vec::new();
+        for v in self.changes_coll().find(
+            Some(doc! { "kind": kind, "name": name.trim() }),
+            Some(options),
+        )? {
+            ret.push(from_document(v?)?);
+        }
+        Ok(ret)
+    }
+
+    /// read the newest table of name from collection
+    pub fn planinfo_read_table(
+        &self,
+        collection: &str,
+        name: &str,
+    ) -> Result<Option<super::planinfo::Table>> {
+        let mut options = mongodb::coll::options::FindOptions::new();
+        options.sort = Some(doc! { "_id": -1 });
+        let context = || format!("{} {}", collection, name);
+        let table = self
             .db()
             .collection(collection)
-            .find_one(Some(bson.clone()), None)
-            .unwrap();
-        if in_cache == None {
-            let ret = self
-                .db()
-                .collection(collection)
-                .insert_one(bson.clone(), None)?;
+            .find_one(Some(doc! { "name": name }), Some(options))
+            .with_context(context)?;
+        match table {
+            Some(table) => Ok(Some(from_document(table).with_context(context)?)),
+            None => Ok(None),
         }
+    }
+
+    /// read the newest dsb of date
+    pub fn dsb_read(&self, date: chrono::NaiveDate) -> Result<Option<super::dsb::DSB>> {
+        dsb_read_coll(&self.dsb_coll(), date)
+    }
+
+    /// read the newest teacher plan of date
+    pub fn teacher_dsb_read(&self, date: chrono::NaiveDate) -> Result<Option<super::dsb::DSB>> {
+        dsb_read_coll(&self.teacher_dsb_coll(), date)
+    }
+
+    /// read the newest table of every name from collection
+    pub fn planinfo_read_tables(&self, collection: &str) -> Result<Vec<super::planinfo::Table>> {
+        let mut options = mongodb::coll::options::FindOptions::new();
+        options.sort = Some(doc! { "_id": -1 });
+        let mut ret: Vec<super::planinfo::Table> = Vec::new();
+        for v in self.db().collection(collection).find(None, Some(options))? {
+            let table: super::planinfo::Table = from_document(v?)?;
+            if !ret.iter().any(|v| v.name == table.name) {
+                ret.push(table);
+            }
+        }
+        Ok(ret)
+    }
+
+    /// write school days, replaces days already in the calendar
+    pub fn calendar_write(&self, days: &[super::calendar::SchoolDay]) -> Result<()> {
+        for day in days.iter() {
+            let filter = doc! { "date": mongodb::to_bson(&day.date)? };
+            let mut options = mongodb::coll::options::ReplaceOptions::new();
+            options.upsert = Some(true);
+            self.calendar_coll()
+                .replace_one(filter, to_document(day)?, Some(options))?;
+        }
+        Ok(())
+    }
+
+    /// read all stored school days
+    pub fn calendar_read(&self) -> Result<Vec<super::calendar::SchoolDay>> {
+        let mut ret = Vec::new();
+        for v in self.calendar_coll().find(None, None)? {
+            ret.push(from_document(v?)?);
+        }
+        Ok(ret)
+    }
+
+    /// replace the exams of date, the latest plan of a day wins
+    pub fn exams_write(&self, date: chrono::NaiveDate, exams: &[super::exams::Exam]) -> Result<()> {
+        self.exams_coll()
+            .delete_many(doc! { "date": mongodb::to_bson(&date)? }, None)?;
+        let mut docs = Vec::new();
+        for v in exams.iter() {
+            docs.push(to_document(v)?);
+        }
+        if !docs.is_empty() {
+            self.exams_coll().insert_many(docs, None)?;
+        }
+        Ok(())
+    }
+    /// read the exams from date on
+    pub fn exams_read(&self, from: chrono::NaiveDate) -> Result<Vec<super::exams::Exam>> {
+        let mut options = mongodb::coll::options::FindOptions::new();
+        options.sort = Some(doc! { "date": 1 });
+        let mut ret = Vec::new();
+        let context = || format!("exams from {}", from);
+        for v in self
+            .exams_coll()
+            .find(
+                Some(doc! { "date": { "$gte": mongodb::to_bson(&from)? } }),
+                Some(options),
+            )
+            .with_context(context)?
+        {
+            ret.push(from_document(v?).with_context(context)?);
+        }
+        Ok(ret)
+    }
+
+    /// replace the index with entries of source
+    /// every entry is upserted by its slot and the entries of older rebuilds are removed afterwards,
+    /// so the index is never empty while it is rebuilt
+    /// entries written before the sources owned their entries are replaced too
+    pub fn index_write(&self, source: &str, entries: &[super::index::Entry]) -> Result<()> {
+        let generation = chrono::Utc::now().timestamp_millis();
+        for v in entries.iter() {
+            let mut doc = to_document(v)?;
+            doc.insert("_id", v.id());
+            doc.insert("generation", generation);
+            let mut options = mongodb::coll::options::ReplaceOptions::new();
+            options.upsert = Some(true);
+            self.index_coll()
+                .replace_one(doc! { "_id": v.id() }, doc, Some(options))?;
+        }
+        self.index_coll().delete_many(
+            doc! {
+                "$or": [ { "source": source }, { "source": { "$exists": false } } ],
+                "generation": { "$ne": generation },
+            },
+            None,
+        )?;
+        Ok(())
+    }
+
+    /// find entries of the index matching query
+    pub fn index_find(&self, query: &super::index::Query) -> Result<Vec<super::index::Entry>> {
+        let mut ret = Vec::new();
+        for v in self.index_coll().find(Some(query.to_doc()), None)? {
+            ret.push(from_document(v?)?);
+        }
+        Ok(ret)
+    }
+
+    /// read the state a source stored to resume its work, like the progress of a crawl
+    /// the state is keyed by the lower case name of the source
+    pub fn state_read<T: DeserializeOwned>(&self, source: &str) -> Result<Option<T>> {
+        let id = source.to_lowercase();
+        match self
+            .crawl_coll()
+            .find_one(Some(doc! { "_id": &id }), None)?
+        {
+            Some(state) => Ok(Some(from_document(state).context(id)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// write the state of a source, replaces the state written before
+    pub fn state_write<T: Serialize>(&self, source: &str, state: &T) -> Result<()> {
+        let id = source.to_lowercase();
+        let mut bson = to_document(state)?;
+        bson.insert("_id", &id);
+        let mut options = mongodb::coll::options::ReplaceOptions::new();
+        options.upsert = Some(true);
+        self.crawl_coll()
+            .replace_one(doc! { "_id": &id }, bson, Some(options))?;
         Ok(())
     }
 }
@@ -0,0 +1,386 @@
+//! test file for the document schema, every stored type is round-tripped through bson and json
+
+use super::{from_document, migrate_dsb, to_document, SCHEMA_VERSION};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+use crate::common::{Course, CourseKind, Hour, Room, Teacher};
+use crate::dsb::{self, EntryKind, Week, DSB};
+use crate::planinfo::Table;
+
+/// wrapper to store values which are no documents, like enums
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Wrap<T> {
+    value: T,
+}
+
+/// encode value as bson and json and decode it again
+fn round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: T) {
+    let value = Wrap { value };
+    let doc = to_document(&value).unwrap();
+    assert_eq!(doc.get_i32("schema").unwrap(), SCHEMA_VERSION);
+    let bson: Wrap<T> = from_document(doc).unwrap();
+    assert_eq!(bson, value);
+
+    let json = serde_json::to_string(&value).unwrap();
+    let json: Wrap<T> = serde_json::from_str(&json).unwrap();
+    assert_eq!(json, value);
+}
+
+fn date() -> chrono::NaiveDate {
+    chrono::NaiveDate::from_ymd(2019, 7, 5)
+}
+
+fn time() -> chrono::NaiveDateTime {
+    date().and_hms(7, 41, 0)
+}
+
+fn courses() -> Vec<Course> {
+    vec![
+        Course::None,
+        Course::Sec1 {
+            name: String::from("M 5a"),
+        },
+        Course::Sec2 {
+            track: 1,
+            name: String::from("D"),
+            kind: CourseKind::GK { number: 11 },
+        },
+        Course::Sec2Exam {
+            track: 2,
+            name: String::from("BI"),
+            kind: CourseKind::LK { number: 1 },
+        },
+        Course::Sec2 {
+            track: 1,
+            name: String::from("KU"),
+            kind: CourseKind::None,
+        },
+        Course::LearningTime {
+            group: String::from("5a"),
+            subject: String::from("M"),
+        },
+        Course::Supervision {
+            group: String::from("BS"),
+            subject: String::from("UEMI"),
+        },
+        Course::WorkingGroup {
+            group: String::from("AG"),
+            subject: String::from("NW4"),
+        },
+    ]
+}
+
+fn rooms() -> Vec<Room> {
+    vec![
+        Room::new(),
+        Room::from_dsb_str("A114"),
+        Room::from_dsb_str("A10a"),
+        Room::from_dsb_str("TH1"),
+        Room {
+            building: String::new(),
+            id: String::from("Sportplatz"),
+            name: Some(String::from("Sportplatz")),
+        },
+    ]
+}
+
+fn hour() -> Hour {
+    Hour {
+        room: Room::from_dsb_str("A114"),
+        teacher: Teacher {
+            name: String::from("OTT"),
+            details: None,
+        },
+        is_tutor: true,
+        course: courses()[2].clone(),
+    }
+}
+
+mod variants {
+    use super::*;
+
+    #[test]
+    fn room() {
+        for v in rooms() {
+            round_trip(v);
+        }
+    }
+
+    #[test]
+    fn course() {
+        for v in courses() {
+            round_trip(v);
+        }
+    }
+
+    #[test]
+    fn teacher_and_hour() {
+        round_trip(Teacher {
+            name: String::from("HERM"),
+            details: None,
+        });
+        round_trip(Hour::new());
+        round_trip(hour());
+    }
+
+    #[test]
+    fn week() {
+        for v in [Week::A, Week::B, Week::NoWeek('C')].iter().cloned() {
+            round_trip(v);
+        }
+    }
+
+    #[test]
+    fn entry_kind() {
+        for v in [
+            EntryKind::Unknow(String::from("foo")),
+            EntryKind::Substitution,
+            EntryKind::Dropped,
+            EntryKind::Special,
+            EntryKind::Changed,
+            EntryKind::Room,
+            EntryKind::RoomSubstitution,
+            EntryKind::Moved,
+            EntryKind::Swap,
+            EntryKind::Exam,
+            EntryKind::DroppedForTeacher,
+            EntryKind::InsteadOf,
+            EntryKind::Release,
+            EntryKind::BreakSupervision,
+            EntryKind::Care,
+        ]
+        .iter()
+        .cloned()
+        {
+            round_trip(v);
+        }
+    }
+
+    #[test]
+    fn week_source() {
+        use crate::calendar::{SchoolDay, WeekSource};
+        for source in [WeekSource::Dsb, WeekSource::Config, WeekSource::Derived]
+            .iter()
+            .cloned()
+        {
+            round_trip(SchoolDay {
+                date: date(),
+                week: Week::B,
+                source,
+            });
+        }
+    }
+}
+
+mod documents {
+    use super::*;
+
+    #[test]
+    fn dsb() {
+        let html = String::from_utf8_lossy(include_bytes!("../dsb/dsb.html"));
+        let mut days = dsb::Config::new().parse(&html).unwrap();
+        days[0].news.push(dsb::mobile::News {
+            date: Some(time()),
+            title: String::from("Elternsprechtag"),
+            detail: String::new(),
+        });
+        days[0].free_lessons = Some(dsb::Duration { from: 5, to: 6 });
+        days[0].duties.push(dsb::duty::Duty {
+            slot: dsb::duty::Slot { before: 3 },
+            location: String::from("Hof"),
+            teacher: Teacher {
+                name: String::from("MUE"),
+                details: None,
+            },
+            substitute: None,
+            message: String::new(),
+        });
+        for day in days {
+            let doc = to_document(&day).unwrap();
+            let back: DSB = from_document(doc).unwrap();
+            assert_eq!(back, day);
+            round_trip(day);
+        }
+    }
+
+    #[test]
+    fn entry() {
+        let mut entry = dsb::Entry::new();
+        entry.name = String::from("5a");
+        entry.course = courses()[1].clone();
+        entry.old_course = courses()[7].clone();
+        entry.time = dsb::Duration { from: 1, to: 2 };
+        entry.new_teacher.name = String::from("OTT");
+        entry.old_teacher.name = String::from("BERG");
+        entry.message = String::from("EVA");
+        entry.kind = EntryKind::Substitution;
+        entry.room = Room::from_dsb_str("B106");
+        round_trip(entry);
+    }
+
+    #[test]
+    fn exam() {
+        let mut entry = dsb::Entry::new();
+        entry.name = String::from("Q1");
+        entry.course = courses()[3].clone();
+        entry.time = dsb::Duration { from: 3, to: 4 };
+        entry.new_teacher.name = String::from("OTT");
+        entry.room = Room::from_dsb_str("A114");
+        let exam = crate::exams::Exam::from_entry(date(), &entry).unwrap();
+        let doc = to_document(&exam).unwrap();
+        let back: crate::exams::Exam = from_document(doc).unwrap();
+        assert_eq!(back, exam);
+        round_trip(exam);
+    }
+
+    #[test]
+    fn table() {
+        let html = format!(
+            "<!DOCTYPE html>\n{}",
+            include_str!("../planinfo/planinfo.html")
+        );
+        let (mut table, _) = crate::planinfo::PlanInfo::new().parse_str(&html).unwrap();
+        table.version = 3;
+        *table.hour_mut(false, 4, 9) = hour();
+        let doc = to_document(&table).unwrap();
+        let back: Table = from_document(doc).unwrap();
+        assert_eq!(back, table);
+        round_trip(table);
+    }
+
+    #[test]
+    fn history_index_and_crawl() {
+        use crate::planinfo::crawl::Crawl;
+        use crate::planinfo::history::Change;
+
+        let old = Table::with_size(5, 10);
+        let mut new = old.clone();
+        *new.hour_mut(true, 0, 0) = hour();
+        round_trip(Change::new("students", &old, &new, time()));
+
+        for mut v in crate::index::entries("students", &new) {
+            v.source = String::from("PlanInfo");
+            round_trip(v);
+        }
+
+        let mut crawl = Crawl::default();
+        crawl.begin(0, time());
+        crawl.hit(12, "room", "A114", time());
+        round_trip(crawl);
+    }
+}
+
+mod schema {
+    use super::*;
+    use bson::{bson, doc};
+
+    #[test]
+    fn legacy_document() {
+        // documents written before versioning have no schema field
+        let doc = doc! { "name": "OTT" };
+        let teacher: Teacher = from_document(doc).unwrap();
+        assert_eq!(teacher.name, "OTT");
+    }
+
+    #[test]
+    fn legacy_room() {
+        // schema 1 stored the rooms as enum
+        let doc = doc! {
+            "room": { "A": { "room": 114 } },
+            "teacher": { "name": "OTT" },
+            "is_tutor": false,
+            "course": "None",
+            "schema": 1,
+        };
+        let hour: Hour = from_document(doc).unwrap();
+        assert_eq!(hour.room, Room::from_dsb_str("A114"));
+    }
+
+    #[test]
+    fn legacy_dsb() {
+        // the first versions stored the info table untyped and without schema
+        let doc = doc! {
+            "school": "Leibniz-Gymnasium",
+            "year": "2018/2019",
+            "valid_from": "2019-07-01",
+            "updated_at": "2019-07-05T07:40:00",
+            "date": "2019-07-05",
+            "week": "B",
+            "FreeLessons": "1-2",
+            "missing_teachers": [{ "name": "OTT" }],
+            "blocked_rooms": [{ "A": { "room": 114 } }, "None"],
+            "affected_classes": [{ "name": "5a" }],
+            "entries": [{
+                "name": "5a",
+                "course": { "Sec1": { "name": "D" } },
+                "old_course": "None",
+                "time": { "from": 3, "to": 3 },
+                "new_teacher": { "name": "MUE" },
+                "old_teacher": { "name": "" },
+                "message": "",
+                "kind": "Substitution",
+                "room": { "B": { "room": 12 } },
+            }],
+        };
+        let day: DSB = from_document(migrate_dsb(doc)).unwrap();
+        assert_eq!(day.date, chrono::NaiveDate::from_ymd(2019, 7, 5));
+        assert_eq!(day.free_lessons, Some(dsb::Duration { from: 1, to: 2 }));
+        assert_eq!(day.missing_teachers.len(), 1);
+        assert_eq!(day.missing_teachers[0].teacher.name, "OTT");
+        assert_eq!(day.missing_teachers[0].time, None);
+        assert_eq!(day.blocked_rooms.len(), 2);
+        assert_eq!(day.blocked_rooms[0].room, Room::from_dsb_str("A114"));
+        assert_eq!(day.blocked_rooms[1].room, Room::new());
+        assert!(day.missing_classes.is_empty());
+        assert!(day.messages.is_empty());
+        assert_eq!(day.entries[0].room, Room::from_dsb_str("B012"));
+    }
+
+    #[test]
+    fn unversioned_current_dsb() {
+        // documents written before versioning may already have the current shape
+        let mut day = dsb::DSB::new();
+        day.free_lessons = Some(dsb::Duration { from: 5, to: 6 });
+        day.missing_teachers.push(dsb::MissingTeacher {
+            teacher: Teacher {
+                name: String::from("OTT"),
+                details: None,
+            },
+            time: Some(dsb::Duration { from: 1, to: 2 }),
+        });
+        day.blocked_rooms.push(dsb::BlockedRoom {
+            room: Room::from_dsb_str("A114"),
+            time: None,
+        });
+        let mut doc = to_document(&day).unwrap();
+        doc.remove("schema");
+        let read: DSB = from_document(migrate_dsb(doc)).unwrap();
+        assert_eq!(read, day);
+    }
+
+    #[test]
+    fn unowned_index_entry() {
+        // entries written before the sources owned them have no source
+        let mut table = Table::with_size(5, 10);
+        *table.hour_mut(true, 0, 0) = hour();
+        let entry = crate::index::entries("teachers", &table).remove(0);
+        let mut doc = to_document(&entry).unwrap();
+        doc.remove("source");
+        doc.remove("schema");
+        let read: crate::index::Entry = from_document(doc).unwrap();
+        assert_eq!(read, entry);
+        assert!(read.source.is_empty());
+    }
+
+    #[test]
+    fn newer_schema() {
+        let doc = doc! { "name": "OTT", "schema": SCHEMA_VERSION + 1 };
+        let err = from_document::<Teacher>(doc).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            crate::error::ErrorKind::SchemaNotSupported(SCHEMA_VERSION + 1)
+        );
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+pub use super::common::{Hour, Teacher, TeacherDetails};
+pub use super::dsb::DSB;
+use super::error::Context;
+pub use super::error::Error;
+#[doc(inline)]
+pub use super::error::Result;
+pub use super::planinfo::Table;
+
+// tests as sub module
+#[cfg(test)] // only add when running tests
+mod test;
+
+/// teacher of the directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// short name as used in the plans (`MUE`)
+    #[serde(default)]
+    pub short: String,
+
+    /// full name (`Anna Müller`)
+    #[serde(default)]
+    pub name: String,
+
+    /// salutation (`Frau`, `Herr`)
+    #[serde(default)]
+    pub salutation: String,
+
+    /// subjects taught (`D`, `GE`)
+    #[serde(default)]
+    pub subjects: Vec<String>,
+
+    #[serde(default)]
+    pub email: Option<String>,
+}
+
+impl Entry {
+    /// get the details to serve, name, salutation and email are only set if public
+    pub fn details(&self, public: bool) -> TeacherDetails {
+        let text = |v: &str| {
+            if public && !v.is_empty() {
+                Some(v.to_string())
+            } else {
+                None
+            }
+        };
+        TeacherDetails {
+            name: text(&self.name),
+            salutation: text(&self.salutation),
+            subjects: self.subjects.clone(),
+            email: if public { self.email.clone() } else { None },
+        }
+    }
+}
+
+/// config struct for the teacher directory
+#[derive(Clone)]
+pub struct Config {
+    /// CSV or TOML files with the teachers
+    pub files: Vec<String>,
+
+    /// expose the full names, salutations and emails of the teachers
+    pub public: bool,
+
+    /// teachers by upper case short name, filled by import
+    pub teachers: BTreeMap<String, Entry>,
+
+    /// verbose level
+    pub verbose: u8,
+}
+
+impl Config {
+    /// create a new instance of Config
+    pub fn new() -> Self {
+        Self {
+            files: Vec::new(),
+            public: false,
+            teachers: BTreeMap::new(),
+            verbose: 0,
+        }
+    }
+
+    /// read the teachers from the files, the format is taken from the extension
+    pub fn import(&mut self) -> Result<()> {
+        for path in self.files.clone().iter() {
+            let content = std::fs::read_to_string(path).context(path)?;
+            let entries = if path.to_lowercase().ends_with(".toml") {
+                parse_toml(&content).context(path)?
+            } else {
+                parse_csv(&content).context(path)?
+            };
+            if self.verbose >= 2 {
+                println!("Debug2: Teachers: {} teachers in {}", entries.len(), path);
+            }
+            for entry in entries {
+                self.insert(entry);
+            }
+        }
+        Ok(())
+    }
+
+    /// add entry to the directory, replaces an entry with the same short name
+    pub fn insert(&mut self, entry: Entry) {
+        self.teachers
+            .insert(entry.short.trim().to_uppercase(), entry);
+    }
+
+    /// get the entry of short name
+    pub fn get(&self, short: &str) -> Option<&Entry> {
+        self.teachers.get(&short.trim().to_uppercase())
+    }
+
+    /// set the details of teacher
+    pub fn apply(&self, teacher: &mut Teacher) {
+        teacher.details = self.get(&teacher.name).map(|v| v.details(self.public));
+    }
+
+    /// set the details of the teacher of hour
+    pub fn apply_hour(&self, hour: &mut Hour) {
+        self.apply(&mut hour.teacher);
+    }
+
+    /// set the details of the teachers of the hours and courses of table
+    pub fn apply_table(&self, table: &mut Table) {
+        for hour in table
+            .table_a
+            .iter_mut()
+            .chain(table.table_b.iter_mut())
+            .flatten()
+        {
+            self.apply_hour(hour);
+        }
+        for course in table.courses.iter_mut() {
+            self.apply(&mut course.teacher);
+        }
+    }
+
+    /// set the details of the teachers of the entries, missing teachers and duties of dsb
+    pub fn apply_dsb(&self, dsb: &mut DSB) {
+        for entry in dsb.entries.iter_mut() {
+            self.apply(&mut entry.new_teacher);
+            self.apply(&mut entry.old_teacher);
+        }
+        for v in dsb.missing_teachers.iter_mut() {
+            self.apply(&mut v.teacher);
+        }
+        for v in dsb.duties.iter_mut() {
+            self.apply(&mut v.teacher);
+            if let Some(substitute) = v.substitute.as_mut() {
+                self.apply(substitute);
+            }
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// parse teachers of a TOML file, tables keyed by the short name
+/// ```toml
+/// [MUE]
+/// name = "Anna Müller"
+/// salutation = "Frau"
+/// subjects = ["D", "GE"]
+/// email = "mue@example.org"
+/// ```
+pub fn parse_toml(input: &str) -> Result<Vec<Entry>> {
+    let teachers: BTreeMap<String, Entry> = toml::from_str(input)?;
+    Ok(teachers
+        .into_iter()
+        .map(|(short, mut entry)| {
+            entry.short = short;
+            entry
+        })
+        .collect())
+}
+
+/// parse teachers of a CSV file separated by `,` or `;`
+/// the header names the columns `short`, `name`, `salutation`, `subjects` and `email`
+/// subjects are separated by spaces
+pub fn parse_csv(input: &str) -> Result<Vec<Entry>> {
+    let mut lines = input.lines().filter(|v| !v.trim().is_empty());
+    let header = match lines.next() {
+        Some(header) => header,
+        None => return Ok(Vec::new()),
+    };
+    let separator = if header.contains(';') { ';' } else { ',' };
+    let header: Vec<String> = split_csv(header, separator)
+        .iter()
+        .map(|v| v.to_lowercase())
+        .collect();
+    let column = |name: &str| header.iter().position(|v| v == name);
+    let short = match column("short") {
+        Some(short) => short,
+        None => {
+            return Err(Error::new_field_not_exists(
+                "teachers csv: short".to_string(),
+            ))
+        }
+    };
+    let (name, salutation, subjects, email) = (
+        column("name"),
+        column("salutation"),
+        column("subjects"),
+        column("email"),
+    );
+
+    let mut ret = Vec::new();
+    for line in lines {
+        let fields = split_csv(line, separator);
+        let field = |column: Option<usize>| -> String {
+            column
+                .and_then(|v| fields.get(v))
+                .map(|v| v.to_string())
+                .unwrap_or_default()
+        };
+        if field(Some(short)).is_empty() {
+            continue;
+        }
+        let email = field(email);
+        ret.push(Entry {
+            short: field(Some(short)),
+            name: field(name),
+            salutation: field(salutation),
+            subjects: field(subjects)
+                .split_whitespace()
+                .map(|v| v.to_string())
+                .collect(),
+            email: if email.is_empty() { None } else { Some(email) },
+        });
+    }
+    Ok(ret)
+}
+
+/// split a CSV line, fields may be quoted with `"`
+fn split_csv(line: &str, separator: char) -> Vec<String> {
+    let mut ret = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == separator && !quoted => {
+                ret.push(field.trim().to_string());
+                field.clear();
+            }
+            c => field.push(c),
+        }
+    }
+    ret.push(field.trim().to_string());
+    ret
+}
@@ -0,0 +1,164 @@
+use super::*;
+
+use crate::common::Hour;
+use crate::dsb::{Entry as DsbEntry, MissingTeacher};
+
+fn config(public: bool) -> Config {
+    let mut conf = Config::new();
+    conf.public = public;
+    for v in parse_csv(
+        "short,name,salutation,subjects,email\nMUE,Anna Müller,Frau,D GE,mue@example.org\n",
+    )
+    .unwrap()
+    {
+        conf.insert(v);
+    }
+    conf
+}
+
+mod parse {
+    use super::*;
+
+    #[test]
+    fn csv() {
+        let input = "Short;Name;Salutation;Subjects;Email\n\
+                     MUE;\"Müller; Anna\";Frau;D GE;mue@example.org\n\
+                     \n\
+                     SCH;\"Bernd \"\"Bob\"\" Schmidt\";Herr;M;\n\
+                     ;Nobody;;;\n";
+        let teachers = parse_csv(input).unwrap();
+        assert_eq!(teachers.len(), 2);
+        assert_eq!(
+            teachers[0],
+            Entry {
+                short: "MUE".to_string(),
+                name: "Müller; Anna".to_string(),
+                salutation: "Frau".to_string(),
+                subjects: vec!["D".to_string(), "GE".to_string()],
+                email: Some("mue@example.org".to_string()),
+            }
+        );
+        assert_eq!(teachers[1].name, "Bernd \"Bob\" Schmidt");
+        assert_eq!(teachers[1].email, None);
+    }
+
+    #[test]
+    fn csv_columns() {
+        let teachers = parse_csv("name,short\nAnna Müller,MUE\n").unwrap();
+        assert_eq!(teachers[0].short, "MUE");
+        assert_eq!(teachers[0].name, "Anna Müller");
+        assert!(teachers[0].subjects.is_empty());
+
+        assert!(parse_csv("name,email\nAnna Müller,mue@example.org\n").is_err());
+        assert!(parse_csv("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn toml() {
+        let input = r#"
+[MUE]
+name = "Anna Müller"
+salutation = "Frau"
+subjects = ["D", "GE"]
+email = "mue@example.org"
+
+[SCH]
+name = "Bernd Schmidt"
+"#;
+        let teachers = parse_toml(input).unwrap();
+        assert_eq!(teachers.len(), 2);
+        assert_eq!(teachers[0].short, "MUE");
+        assert_eq!(teachers[0].subjects, vec!["D", "GE"]);
+        assert_eq!(teachers[1].short, "SCH");
+        assert_eq!(teachers[1].salutation, "");
+        assert_eq!(teachers[1].email, None);
+
+        assert!(parse_toml("[MUE]\nname = 1").is_err());
+    }
+}
+
+mod apply {
+    use super::*;
+
+    #[test]
+    fn public() {
+        let conf = config(true);
+        let mut teacher = Teacher {
+            name: "mue".to_string(),
+            details: None,
+        };
+        conf.apply(&mut teacher);
+        let details = teacher.details.as_ref().unwrap();
+        assert_eq!(details.name, Some("Anna Müller".to_string()));
+        assert_eq!(details.salutation, Some("Frau".to_string()));
+        assert_eq!(details.email, Some("mue@example.org".to_string()));
+        assert_eq!(details.subjects, vec!["D", "GE"]);
+        assert_eq!(teacher.display_name(), "Anna Müller");
+    }
+
+    #[test]
+    fn private() {
+        let conf = config(false);
+        let mut teacher = Teacher {
+            name: "MUE".to_string(),
+            details: None,
+        };
+        conf.apply(&mut teacher);
+        let details = teacher.details.as_ref().unwrap();
+        assert_eq!(details.name, None);
+        assert_eq!(details.salutation, None);
+        assert_eq!(details.email, None);
+        assert_eq!(details.subjects, vec!["D", "GE"]);
+        assert_eq!(teacher.display_name(), "MUE");
+    }
+
+    #[test]
+    fn unknown() {
+        let conf = config(true);
+        let mut teacher = Teacher {
+            name: "XYZ".to_string(),
+            details: None,
+        };
+        conf.apply(&mut teacher);
+        assert_eq!(teacher.details, None);
+        assert_eq!(teacher.display_name(), "XYZ");
+    }
+
+    #[test]
+    fn dsb() {
+        let conf = config(true);
+        let mut dsb = DSB::new();
+        let mut entry = DsbEntry::new();
+        entry.new_teacher.name = "MUE".to_string();
+        dsb.entries.push(entry);
+        dsb.missing_teachers.push(MissingTeacher {
+            teacher: Teacher {
+                name: "MUE".to_string(),
+                details: None,
+            },
+            time: None,
+        });
+        conf.apply_dsb(&mut dsb);
+        assert_eq!(dsb.entries[0].new_teacher.display_name(), "Anna Müller");
+        assert_eq!(dsb.entries[0].old_teacher.details, None);
+        assert_eq!(
+            dsb.missing_teachers[0].teacher.display_name(),
+            "Anna Müller"
+        );
+    }
+
+    #[test]
+    fn table() {
+        let conf = config(false);
+        let mut hour = Hour::new();
+        hour.teacher.name = "MUE".to_string();
+        let mut table = Table::new();
+        table.table_a = vec![vec![hour.clone()]];
+        table.table_b = vec![vec![Hour::new(), hour]];
+        conf.apply_table(&mut table);
+        assert!(table.table_a[0][0].teacher.details.is_some());
+        assert!(table.table_b[0][0].teacher.details.is_none());
+        assert!(table.table_b[0][1].teacher.details.is_some());
+        assert_eq!(table.table_b[0][1].teacher.display_name(), "MUE");
+    }
+}